@@ -1,32 +1,124 @@
-use std::{env, fs, fs::File, io::Write, path::Path};
+use std::{
+    env, fs,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 fn main() {
-    let file_paths = fs::read_dir("default-plugins")
-        .unwrap()
-        .filter_map(|e| e.ok())
-        .collect::<Vec<_>>();
+    let root = Path::new("default-plugins");
+    let mut plugins = Vec::new();
+    let mut bundled_files = Vec::new();
+
+    for path in entries(root) {
+        if path.is_dir() {
+            // a plugin bundle, holding the plugin itself and any files it uses.
+            collect_bundle(&path, &mut plugins, &mut bundled_files);
+        } else {
+            plugins.push(path);
+        }
+    }
+
     let out_dir = env::var("OUT_DIR").unwrap();
     let mut plugins_file = File::create(Path::new(&out_dir).join("plugins.include")).unwrap();
 
     _ = plugins_file.write_fmt(format_args!(
-        r#"/// Generated at compile time, contains the raw bytes of the files in the repository in
-/// `default-plugins/`.
-pub const DEFAULT_PLUGINS_BYTES: [&[u8]; {}] = [
+        r#"/// Generated at compile time, contains the paths, relative to `default-plugins/` in the
+/// repository, and raw bytes of the plugins in that directory and its plugin bundles.
+pub const DEFAULT_PLUGINS: [(&str, &[u8]); {}] = [
 "#,
-        file_paths.len()
+        plugins.len()
     ));
 
-    for path in file_paths {
-        let path = path.path();
+    for path in plugins {
+        plugins_file
+            .write_fmt(format_args!(
+                "({:?}, &{:?}),\n",
+                relative_path(root, &path),
+                read(&path)
+            ))
+            .unwrap()
+    }
+
+    _ = plugins_file.write_fmt(format_args!(
+        r#"];
 
-        let contents = fs::read(&path)
-            .map_err(|error| Err::<(), String>(format!("could read file at \"{path:?}\": {error}")))
-            .unwrap();
+/// Generated at compile time, contains the paths, relative to `default-plugins/` in the
+/// repository, raw bytes, and whether they are executable, of the files in the plugin
+/// bundles in that directory.
+pub const DEFAULT_PLUGIN_FILES: [(&str, &[u8], bool); {}] = [
+"#,
+        bundled_files.len()
+    ));
 
+    for path in bundled_files {
         plugins_file
-            .write_fmt(format_args!("&{contents:?},\n"))
+            .write_fmt(format_args!(
+                "({:?}, &{:?}, {}),\n",
+                relative_path(root, &path),
+                read(&path),
+                is_executable(&path)
+            ))
             .unwrap()
     }
 
     _ = plugins_file.write("];".as_bytes()).unwrap();
 }
+
+/// Adds the `.ron` files directly in the bundle at `dir` to `plugins`, and every other
+/// file in the bundle to `bundled_files`.
+fn collect_bundle(dir: &Path, plugins: &mut Vec<PathBuf>, bundled_files: &mut Vec<PathBuf>) {
+    for path in entries(dir) {
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "ron") {
+            plugins.push(path);
+        } else {
+            collect_files(&path, bundled_files);
+        }
+    }
+}
+
+/// Adds `path` to `files` if it's a file, or every file in it if it's a directory.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        entries(path)
+            .iter()
+            .for_each(|path| collect_files(path, files));
+    } else {
+        files.push(path.to_owned());
+    }
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect()
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    fs::read(path)
+        .map_err(|error| Err::<(), String>(format!("could read file at \"{path:?}\": {error}")))
+        .unwrap()
+}
+
+/// The path of `path` relative to `root`, always separated with `/` so that the embedded
+/// paths are the same on every platform.
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap()
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool { false }
@@ -0,0 +1,617 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{
+    builder::FalseyValueParser, error::ErrorKind, Args, CommandFactory, Parser, Subcommand,
+};
+use proman::config::{
+    self, Answers, CommandStep, Error, Frontend, GivenAnswer, LanguageConfig, LanguageConfigRunner,
+    Migration, PluginLoader, PluginScope, RunnerError, RunningConfigMessage, Validation,
+    SCHEMA_VERSION,
+};
+
+use crate::headless;
+
+/// A simple and configurable, terminal-based application to create new programming
+/// projects. Without a command, the TUI is started.
+#[derive(Debug, Parser)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Cli {
+    /// Runs the plugin for LANGUAGE in dry-run mode without the TUI, asking its prompts
+    /// on stdin. The same as `proman new LANGUAGE --dry-run --interactive`.
+    #[arg(long, value_name = "LANGUAGE")]
+    pub(crate) dry_run: Option<String>,
+    /// Answers prompts with the answers in a RON or JSON file, rather than asking them.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) answers: Option<PathBuf>,
+    /// Leaves out the plugins built into proman, so that only the plugins in the plugin
+    /// directories can be used.
+    #[arg(
+        long,
+        global = true,
+        env = "PROMAN_NO_BUILTIN_PLUGINS",
+        value_parser = FalseyValueParser::new()
+    )]
+    no_builtin_plugins: bool,
+    /// Loads plugins from DIR before any other plugin directory. Can be given more than
+    /// once, with the first taking precedence.
+    #[arg(long, global = true, value_name = "DIR", value_parser = parse_plugin_dir)]
+    plugin_dir:         Vec<PathBuf>,
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// Creates a new project with the plugin for a language, without the TUI.
+    New(NewArgs),
+    /// Adds members to the project in the current directory, such as the crates of a
+    /// Cargo workspace or the modules of a Go workspace, without the TUI.
+    Add(AddArgs),
+    /// Lists the plugins that were loaded, and what is wrong with those that couldn't be.
+    /// Exits with 1 if any couldn't be loaded.
+    Plugins(PluginsArgs),
+}
+
+/// The arguments of `proman plugins`.
+#[derive(Debug, Args)]
+pub(crate) struct PluginsArgs {
+    #[command(subcommand)]
+    command: Option<PluginsCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum PluginsCommand {
+    /// Rewrites plugin files written for an older version of the plugin schema in place,
+    /// keeping their comments and layout. Exits with 1 if any couldn't be migrated.
+    Migrate(MigrateArgs),
+}
+
+/// The arguments of `proman plugins migrate`.
+#[derive(Debug, Args)]
+pub(crate) struct MigrateArgs {
+    /// The plugin files to migrate. Without any, every plugin in the plugin directories
+    /// is migrated.
+    paths:   Vec<PathBuf>,
+    /// Shows which plugins would be migrated, without changing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Cli {
+    /// Parses the arguments, exiting with a usage error if they can't be used together.
+    /// `--dry-run` and `--answers` only apply to the TUI, but the global flags can be
+    /// given before a command.
+    pub(crate) fn parse_args() -> Self {
+        let cli = Self::parse();
+
+        if cli.command.is_some() && (cli.dry_run.is_some() || cli.answers.is_some()) {
+            Self::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "`--dry-run` and `--answers` can't be given before a command, give the \
+                     command's own flags after it instead",
+                )
+                .exit();
+        }
+
+        cli
+    }
+
+    /// The loader for the plugins that the arguments ask for.
+    pub(crate) fn plugin_loader(&self) -> config::Result<PluginLoader> {
+        let loader = self
+            .plugin_dir
+            .iter()
+            .fold(PluginLoader::new()?, |loader, dir| {
+                loader.with_dir(PluginScope::Flag, dir)
+            });
+
+        Ok(loader.with_builtins(!self.no_builtin_plugins))
+    }
+}
+
+/// The arguments of `proman new`. Every prompt of the plugin has to be answered with
+/// them, unless `--interactive` is given.
+#[derive(Debug, Args)]
+pub(crate) struct NewArgs {
+    /// The language of the plugin to run, ignoring case.
+    language:     String,
+    /// The name of the project. The same as `--set project_name=NAME`.
+    #[arg(long)]
+    name:         Option<String>,
+    /// The type of the project, such as `binary` or `library`. The same as
+    /// `--set project_type=TYPE`.
+    #[arg(long = "type", value_name = "TYPE")]
+    project_type: Option<String>,
+    /// Answers the prompt that stores its answer in KEY. Choices are given as one of the
+    /// options, confirmations as yes or no, and multi-selects as options separated by
+    /// commas.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_answer)]
+    set:          Vec<(String, String)>,
+    /// Answers prompts with the answers in a RON or JSON file. Answers given with the
+    /// other flags take precedence.
+    #[arg(long, value_name = "FILE")]
+    answers:      Option<PathBuf>,
+    /// Asks the prompts that weren't answered on stdin, rather than failing.
+    #[arg(short, long)]
+    interactive:  bool,
+    /// Shows what would be done, without doing it.
+    #[arg(long)]
+    dry_run:      bool,
+}
+
+/// The arguments of `proman add`. The members have to be given with them, unless
+/// `--interactive` is given.
+#[derive(Debug, Args)]
+pub(crate) struct AddArgs {
+    /// The language of the plugin the project was made with, ignoring case. Found from
+    /// the files in the project if it isn't given.
+    language:    Option<String>,
+    /// The name of the member to add. The same as `--set members=NAME`.
+    #[arg(long)]
+    name:        Option<String>,
+    /// The type of the member, such as `binary` or `library`. Used with `--name`.
+    #[arg(long = "type", value_name = "TYPE", requires = "name")]
+    member_type: Option<String>,
+    /// Answers the prompt that stores its answer in KEY, such as `members` for a list of
+    /// members given as NAME or NAME:TYPE, separated by commas.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_answer)]
+    set:         Vec<(String, String)>,
+    /// Answers prompts with the answers in a RON or JSON file. Answers given with the
+    /// other flags take precedence.
+    #[arg(long, value_name = "FILE")]
+    answers:     Option<PathBuf>,
+    /// Asks the prompts that weren't answered on stdin, rather than failing.
+    #[arg(short, long)]
+    interactive: bool,
+    /// Shows what would be done, without doing it.
+    #[arg(long)]
+    dry_run:     bool,
+}
+
+/// The exit codes of `proman`, besides 0 for success.
+mod exit_code {
+    /// A step of the run failed, or something else went wrong.
+    pub(crate) const FAILURE: u8 = 1;
+    /// The arguments were wrong, such as a language without a plugin.
+    pub(crate) const USAGE: u8 = 2;
+    /// A prompt wasn't answered, or its answer couldn't be used.
+    pub(crate) const ANSWER: u8 = 3;
+    /// The tools the plugin requires aren't all installed.
+    pub(crate) const REQUIREMENTS: u8 = 4;
+    /// The run was cancelled, such as by pressing ctrl+c.
+    pub(crate) const CANCELLED: u8 = 130;
+}
+
+/// Parses an answer given with `--set`, such as `runtime=tokio`.
+fn parse_answer(assignment: &str) -> Result<(String, String), String> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() =>
+            Ok((key.trim().to_owned(), value.to_owned())),
+        _ => Err(format!("expected KEY=VALUE, got \"{assignment}\"")),
+    }
+}
+
+/// Parses a directory given with `--plugin-dir`, which has to exist.
+fn parse_plugin_dir(dir: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(dir);
+
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Err(format!("\"{dir}\" isn't a directory"))
+    }
+}
+
+/// Runs `proman new`, printing the output of the run to stdout, and returns the exit code
+/// for how it went.
+pub(crate) fn new(args: NewArgs, loader: &PluginLoader) -> ExitCode {
+    match run_new(args, loader) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => report(&error),
+    }
+}
+
+fn run_new(args: NewArgs, loader: &PluginLoader) -> config::Result<()> {
+    let config = loader.load()?.find(&args.language)?;
+
+    let mut flags = Answers::new();
+    for (variable, answer) in args.set {
+        flags.insert(variable, GivenAnswer::Text(answer));
+    }
+    if let Some(name) = args.name {
+        flags.insert("project_name", GivenAnswer::Text(name));
+    }
+    if let Some(project_type) = args.project_type {
+        flags.insert("project_type", GivenAnswer::Text(project_type));
+    }
+
+    check_variables(&config, config.command_steps(), &[], &flags)?;
+
+    let mut answers = load_answers(args.answers.as_deref())?;
+    answers.extend(flags);
+
+    let runner = config
+        .create_runner()
+        .with_dry_run(args.dry_run)
+        .with_answers(answers);
+    let plan = headless::run(runner, args.interactive)?;
+
+    if args.dry_run {
+        headless::print_plan(&config, &plan);
+    }
+
+    Ok(())
+}
+
+/// Runs `proman add`, printing the output of the run to stdout, and returns the exit code
+/// for how it went.
+pub(crate) fn add(args: AddArgs, loader: &PluginLoader) -> ExitCode {
+    match run_add(args, loader) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => report(&error),
+    }
+}
+
+fn run_add(args: AddArgs, loader: &PluginLoader) -> config::Result<()> {
+    let dir = env::current_dir()?;
+    let plugins = loader.load()?;
+    let config = match args.language {
+        Some(ref language) => plugins.find(language)?,
+        None => plugins.detect_project(&dir)?,
+    };
+
+    let mut flags = Answers::new();
+    for (variable, answer) in args.set {
+        flags.insert(variable, GivenAnswer::Text(answer));
+    }
+    if let Some(name) = args.name {
+        let member = match args.member_type {
+            Some(member_type) => format!("{name}:{member_type}"),
+            None => name,
+        };
+
+        flags.insert("members", GivenAnswer::Text(member));
+    }
+
+    // the members are always read from `members` when adding to a project, whether or
+    // not the plugin has a `CreateMembers` step.
+    let mut steps = config.add_member().to_vec();
+    steps.extend(config.command_steps().iter().cloned());
+    check_variables(&config, &steps, &["members"], &flags)?;
+
+    let mut answers = load_answers(args.answers.as_deref())?;
+    answers.extend(flags);
+
+    let runner = config
+        .create_runner()
+        .with_existing_project(dir)
+        .with_dry_run(args.dry_run)
+        .with_answers(answers);
+    let plan = headless::run(runner, args.interactive)?;
+
+    if args.dry_run {
+        headless::print_plan(&config, &plan);
+    }
+
+    Ok(())
+}
+
+/// Runs `proman plugins`, or the subcommand given with it, and returns the exit code for
+/// how it went.
+pub(crate) fn plugins(args: PluginsArgs, loader: &PluginLoader) -> ExitCode {
+    match args.command {
+        Some(PluginsCommand::Migrate(args)) => migrate(args, loader),
+        None => list_plugins(loader),
+    }
+}
+
+/// Prints the plugins that were loaded, the plugins they override and the problems with
+/// those that couldn't be loaded, and returns the exit code for whether there were any
+/// problems.
+fn list_plugins(loader: &PluginLoader) -> ExitCode {
+    let loaded = match loader.load() {
+        Ok(loaded) => loaded,
+        Err(error) => return report(&error),
+    };
+
+    println!("Plugin directories, from the highest precedence:");
+    for (scope, dir) in loader.dirs() {
+        let missing = if dir.is_dir() { "" } else { " (doesn't exist)" };
+
+        println!("  {:<18} {}{missing}", scope.to_string(), dir.display());
+    }
+    if !loader.builtins() {
+        println!("  (the built-in plugins are disabled)");
+    }
+
+    println!();
+    println!("Loaded plugins:");
+    for config in loaded.configs() {
+        match config.source() {
+            Some(source) => println!("  {:<16} {source}", config.language()),
+            None => println!("  {}", config.language()),
+        }
+
+        for shadowed in loaded.shadowed(config) {
+            println!("  {:<16} overrides {shadowed}", "");
+        }
+        if config.schema_version() < SCHEMA_VERSION {
+            println!(
+                "  {:<16} written for schema {}, `proman plugins migrate` updates it",
+                "",
+                config.schema_version()
+            );
+        }
+    }
+
+    if loaded.diagnostics().is_empty() {
+        return ExitCode::SUCCESS;
+    }
+
+    println!();
+    println!("Plugins that couldn't be loaded:");
+    for diagnostic in loaded.diagnostics() {
+        for line in diagnostic.to_string().lines() {
+            println!("  {line}");
+        }
+    }
+
+    ExitCode::from(exit_code::FAILURE)
+}
+
+/// Runs `proman plugins migrate`, printing the plugins that were migrated and the
+/// problems with those that couldn't be, and returns the exit code for whether there were
+/// any problems.
+fn migrate(args: MigrateArgs, loader: &PluginLoader) -> ExitCode {
+    let migrated = if args.paths.is_empty() {
+        match loader.migrate(args.dry_run) {
+            Ok(migrated) => migrated,
+            Err(error) => return report(&error),
+        }
+    } else {
+        args.paths
+            .into_iter()
+            .map(|path| {
+                let migration = config::migrate_plugin_file(&path, args.dry_run);
+
+                (path, migration)
+            })
+            .collect()
+    };
+
+    let mut up_to_date = 0;
+    let mut failed = false;
+
+    for (path, migration) in migrated {
+        match migration {
+            Ok(Migration::UpToDate) => up_to_date += 1,
+            Ok(Migration::Migrated(from)) if args.dry_run => println!(
+                "Would migrate {} from schema {from} to {SCHEMA_VERSION}",
+                path.display()
+            ),
+            Ok(Migration::Migrated(from)) => println!(
+                "Migrated {} from schema {from} to {SCHEMA_VERSION}",
+                path.display()
+            ),
+            Err(diagnostic) => {
+                eprintln!("error: could not migrate the plugin");
+                for line in diagnostic.to_string().lines() {
+                    eprintln!("  {line}");
+                }
+                failed = true;
+            },
+        }
+    }
+
+    if up_to_date > 0 {
+        println!("{up_to_date} plugin(s) already up to date");
+    }
+
+    if failed {
+        ExitCode::from(exit_code::FAILURE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Checks that each of the answers given with flags answers a prompt of `steps`, or is
+/// one of the `known` variables that the run reads without a prompt. An answer for a
+/// prompt the plugin doesn't have is most likely a typo, which would otherwise only be
+/// noticed once the prompt is found to be unanswered. Answers files aren't checked, as
+/// they can be shared between plugins.
+fn check_variables(
+    config: &LanguageConfig,
+    steps: &[CommandStep],
+    known: &[&str],
+    flags: &Answers,
+) -> config::Result<()> {
+    let variables = steps
+        .iter()
+        .filter_map(|step| step.command().variable())
+        .chain(known.iter().copied())
+        .collect::<Vec<_>>();
+
+    match flags
+        .variables()
+        .find(|variable| !variables.contains(variable))
+    {
+        Some(unknown) => Err(Error::UnknownVariable {
+            language: config.language().to_owned(),
+            variable: unknown.to_owned(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Reads the answers file at `path`, if there is one.
+pub(crate) fn load_answers(path: Option<&Path>) -> config::Result<Answers> {
+    path.map_or_else(|| Ok(Answers::new()), Answers::load)
+}
+
+/// Offers to save the answers collected by `runner` to a file, so that they can be given
+/// again with `--answers`. Nothing is offered if every answer was given before the run.
+pub(crate) fn offer_to_save_answers(frontend: &mut dyn Frontend, runner: &LanguageConfigRunner) {
+    let collected = runner.collected_answers();
+    let given = runner.answers();
+
+    if collected
+        .variables()
+        .all(|variable| collected.get(variable) == given.get(variable))
+    {
+        return;
+    }
+
+    if frontend.ask_confirm("Save these answers to a file?", false) != Some(true) {
+        return;
+    }
+
+    let default = format!("{}-answers.ron", runner.config().language().to_lowercase());
+    let Some(path) = frontend.ask_text(
+        "Answers file (.ron or .json)",
+        Some(&default),
+        &Validation::default(),
+    ) else {
+        return;
+    };
+
+    frontend.show(RunningConfigMessage::CommandOutput(
+        match collected.save(Path::new(&path)) {
+            Ok(()) => format!("Saved the answers to {path}, use them with --answers {path}"),
+            Err(error) => error.to_string(),
+        },
+    ));
+}
+
+/// Prints `error` to stderr, returning the exit code for it.
+pub(crate) fn report(error: &Error) -> ExitCode {
+    // the errors of a run make sense without saying that they came from the runner.
+    match error {
+        Error::Runner(error) => eprintln!("proman: {error}"),
+        _ => eprintln!("proman: {error}"),
+    }
+
+    let code = match error {
+        Error::AmbiguousProject { .. } => {
+            eprintln!("Choose one with `proman add LANGUAGE`");
+            exit_code::USAGE
+        },
+        Error::Runner(RunnerError::MissingAnswer { variable, .. }) => {
+            eprintln!("Answer it with `--set {variable}=...`, or pass `--interactive`");
+            exit_code::ANSWER
+        },
+        Error::Runner(RunnerError::InvalidAnswer { .. }) => exit_code::ANSWER,
+        Error::Runner(RunnerError::UnmetRequirements(..)) => exit_code::REQUIREMENTS,
+        Error::Runner(RunnerError::Cancelled) => exit_code::CANCELLED,
+        Error::UnknownLanguage(..) | Error::UnknownVariable { .. } => exit_code::USAGE,
+        Error::NoProjectFound(..) => exit_code::USAGE,
+        Error::Runner(RunnerError::ProjectNotFound(..) | RunnerError::CannotAddMembers) =>
+            exit_code::USAGE,
+        Error::CouldNotReadAnswers { .. } => exit_code::USAGE,
+        _ => exit_code::FAILURE,
+    };
+
+    ExitCode::from(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(source: &str) -> LanguageConfig {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .unwrap()
+    }
+
+    fn flags(variable: &str) -> Answers {
+        let mut flags = Answers::new();
+        flags.insert(variable, GivenAnswer::Text(String::from("x")));
+        flags
+    }
+
+    #[test]
+    fn checks_that_flags_answer_a_prompt() {
+        let config = plugin(
+            r#"(language: "M", command_steps: [Step(name: "name", command: PromptProjectName)])"#,
+        );
+
+        assert!(
+            check_variables(&config, config.command_steps(), &[], &flags("project_name")).is_ok()
+        );
+        assert!(matches!(
+            check_variables(&config, config.command_steps(), &[], &flags("project_nmae")),
+            Err(Error::UnknownVariable { variable, .. }) if variable == "project_nmae"
+        ));
+        assert!(check_variables(
+            &config,
+            config.command_steps(),
+            &["members"],
+            &flags("members")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn parses_answers_given_with_set() {
+        assert_eq!(
+            parse_answer(" runtime =tokio = yes"),
+            Ok((String::from("runtime"), String::from("tokio = yes")))
+        );
+        assert_eq!(
+            parse_answer("docker="),
+            Ok((String::from("docker"), String::new()))
+        );
+        assert!(parse_answer("runtime").is_err());
+        assert!(parse_answer(" =tokio").is_err());
+    }
+
+    #[test]
+    fn reports_errors_with_their_exit_codes() {
+        let code = |error: Error| report(&error);
+        let runner = |error: RunnerError| code(Error::Runner(error));
+
+        assert_eq!(
+            code(Error::UnknownLanguage(String::from("cobol"))),
+            ExitCode::from(exit_code::USAGE)
+        );
+        assert_eq!(
+            code(Error::NoProjectFound(PathBuf::from("."))),
+            ExitCode::from(exit_code::USAGE)
+        );
+        assert_eq!(
+            runner(RunnerError::MissingAnswer {
+                variable: String::from("project_name"),
+                label:    String::from("Project name"),
+            }),
+            ExitCode::from(exit_code::ANSWER)
+        );
+        assert_eq!(
+            runner(RunnerError::InvalidAnswer {
+                answer: String::from("../x"),
+                reason: String::new(),
+            }),
+            ExitCode::from(exit_code::ANSWER)
+        );
+        assert_eq!(
+            runner(RunnerError::UnmetRequirements(Vec::new())),
+            ExitCode::from(exit_code::REQUIREMENTS)
+        );
+        assert_eq!(
+            runner(RunnerError::Cancelled),
+            ExitCode::from(exit_code::CANCELLED)
+        );
+        assert_eq!(
+            runner(RunnerError::TimedOut {
+                command: String::from("cargo"),
+                seconds: 1,
+            }),
+            ExitCode::from(exit_code::FAILURE)
+        );
+    }
+}
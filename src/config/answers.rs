@@ -0,0 +1,249 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use minijinja::value::{Value, ValueKind};
+
+use super::{Error, ProjectType, Result};
+
+/// An answer to a prompt that was given before the run, such as in an answers file or on
+/// the command line.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum GivenAnswer {
+    /// The answer to a confirmation.
+    Bool(bool),
+    /// The options selected in a multi-select.
+    List(Vec<String>),
+    /// The answer to a text prompt, or the option chosen in a choice. Confirmations and
+    /// multi-selects can be given as text too, as described by
+    /// [`super::Question::parse`].
+    Text(String),
+}
+
+impl From<&Value> for GivenAnswer {
+    fn from(value: &Value) -> Self {
+        match value.kind() {
+            ValueKind::Bool => Self::Bool(value.is_true()),
+            ValueKind::Seq => Self::List(
+                value
+                    .try_iter()
+                    .map(|items| items.map(|item| item.to_string()).collect())
+                    .unwrap_or_default(),
+            ),
+            _ => Self::Text(value.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for GivenAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(true) => f.write_str("yes"),
+            Self::Bool(false) => f.write_str("no"),
+            Self::List(selected) => f.write_str(&selected.join(", ")),
+            Self::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+/// Answers to the prompts of a plugin, by the variable of the prompt, which the project
+/// name and type are answered by with `project_name` and `project_type`. Answers can be
+/// read from and saved to RON or JSON files, so that a run can be repeated:
+///
+/// ```ron
+/// (
+///     project_name: "my-tool",
+///     project_type: Library,
+///     prompts: {
+///         "runtime": "tokio",
+///         "docker": true,
+///     },
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "AnswersFile", into = "AnswersFile")]
+pub struct Answers(BTreeMap<String, GivenAnswer>);
+
+/// How [`Answers`] are written in a file. The project name and type are given on their
+/// own, so that the type can be written as it is in plugins. Any other field is most
+/// likely an answer that was meant to be in `prompts`, so it is an error rather than
+/// being dropped.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Answers", deny_unknown_fields)]
+struct AnswersFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project_type: Option<ProjectType>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    prompts:      BTreeMap<String, GivenAnswer>,
+}
+
+impl From<AnswersFile> for Answers {
+    fn from(file: AnswersFile) -> Self {
+        let mut answers = Answers(file.prompts);
+
+        if let Some(name) = file.project_name {
+            answers.insert("project_name", GivenAnswer::Text(name));
+        }
+
+        if let Some(project_type) = file.project_type {
+            answers.insert("project_type", GivenAnswer::Text(project_type.to_string()));
+        }
+
+        answers
+    }
+}
+
+impl From<Answers> for AnswersFile {
+    fn from(mut answers: Answers) -> Self {
+        let project_name = match answers.0.remove("project_name") {
+            Some(GivenAnswer::Text(name)) => Some(name),
+            Some(other) => {
+                answers.0.insert(String::from("project_name"), other);
+                None
+            },
+            None => None,
+        };
+
+        // a type that isn't one of the known types is kept with the other prompts, where
+        // it's still read back as the answer for the project type.
+        let project_type = match answers.0.remove("project_type") {
+            Some(GivenAnswer::Text(text)) => {
+                let known = [
+                    ProjectType::Binary,
+                    ProjectType::Library,
+                    ProjectType::Workspace,
+                ]
+                .into_iter()
+                .find(|project_type| project_type.to_string().eq_ignore_ascii_case(&text));
+
+                if known.is_none() {
+                    answers
+                        .0
+                        .insert(String::from("project_type"), GivenAnswer::Text(text));
+                }
+
+                known
+            },
+            Some(other) => {
+                answers.0.insert(String::from("project_type"), other);
+                None
+            },
+            None => None,
+        };
+
+        AnswersFile {
+            project_name,
+            project_type,
+            prompts: answers.0,
+        }
+    }
+}
+
+impl Answers {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn get(&self, variable: &str) -> Option<&GivenAnswer> { self.0.get(variable) }
+
+    pub fn insert(&mut self, variable: impl Into<String>, answer: GivenAnswer) {
+        self.0.insert(variable.into(), answer);
+    }
+
+    /// Adds the answers in `other`, replacing any answers to the same prompts.
+    pub fn extend(&mut self, other: Answers) { self.0.extend(other.0); }
+
+    /// The variables of the prompts that have been answered.
+    pub fn variables(&self) -> impl Iterator<Item = &str> { self.0.keys().map(String::as_str) }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Reads the answers in the file at `path`, which is read as JSON if its extension is
+    /// `.json`, or RON otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let could_not_read = |reason: String| Error::CouldNotReadAnswers {
+            path: path.to_owned(),
+            reason,
+        };
+
+        let contents =
+            fs::read_to_string(path).map_err(|error| could_not_read(error.to_string()))?;
+
+        if is_json(path) {
+            serde_json::from_str(&contents).map_err(|error| could_not_read(error.to_string()))
+        } else {
+            ron_options()
+                .from_str(&contents)
+                .map_err(|error| could_not_read(error.to_string()))
+        }
+    }
+
+    /// Writes the answers to the file at `path`, as JSON if its extension is `.json`, or
+    /// RON otherwise.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let could_not_write = |reason: String| Error::CouldNotWriteAnswers {
+            path: path.to_owned(),
+            reason,
+        };
+
+        let contents = if is_json(path) {
+            serde_json::to_string_pretty(self)
+                .map_err(|error| could_not_write(error.to_string()))?
+        } else {
+            ron_options()
+                .to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|error| could_not_write(error.to_string()))?
+        };
+
+        fs::write(path, contents + "\n").map_err(|error| could_not_write(error.to_string()))
+    }
+}
+
+/// Answers files are read and written like plugins, so that optional answers don't have
+/// to be wrapped in `Some(...)`.
+fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_answers() {
+        let answers: Answers = ron_options()
+            .from_str(
+                r#"(project_name: "my-tool", project_type: Library, prompts: {"docker": true})"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            answers.get("project_name"),
+            Some(&GivenAnswer::Text(String::from("my-tool")))
+        );
+        assert_eq!(
+            answers.get("project_type"),
+            Some(&GivenAnswer::Text(String::from("Library")))
+        );
+        assert_eq!(answers.get("docker"), Some(&GivenAnswer::Bool(true)));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let error = ron_options()
+            .from_str::<Answers>(r#"(project_name: "my-tool", docker: true)"#)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("docker"), "{error}");
+
+        let error =
+            serde_json::from_str::<Answers>(r#"{"project_name": "my-tool", "docker": true}"#)
+                .unwrap_err();
+
+        assert!(error.to_string().contains("docker"), "{error}");
+    }
+}
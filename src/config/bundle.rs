@@ -0,0 +1,189 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The name of the directory in a plugin bundle that [`super::CommandType::CopyTemplate`]
+/// copies into the project.
+pub(crate) const TEMPLATE_DIR: &str = "template";
+
+/// The extension of the files in a template directory whose contents are rendered, which
+/// is removed from their names when they're copied. Other files are copied as they are.
+pub(crate) const TEMPLATE_EXTENSION: &str = "jinja";
+
+/// Where a plugin was loaded from. Plugins can be a single `.ron` file, or a bundle: a
+/// directory holding the `.ron` file along with any files it uses, such as a `template/`
+/// tree.
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum PluginSource {
+    /// A default plugin, built into the application by the build script. The path is
+    /// relative to the `default-plugins` directory.
+    BuiltIn(PathBuf),
+    /// A plugin loaded from the filesystem, from a plugin directory of the given scope.
+    File(PathBuf, PluginScope),
+}
+
+/// The kinds of places plugins are loaded from, in order of precedence. A plugin for a
+/// language overrides the plugins for the same language from the scopes after it.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum PluginScope {
+    /// A directory given with `--plugin-dir`.
+    Flag,
+    /// A directory in the [`PLUGIN_PATH_VAR`](super::PLUGIN_PATH_VAR) environment
+    /// variable.
+    Environment,
+    /// The `.proman/plugins` directory of the project `proman` is run in.
+    Project,
+    /// The user's plugin directory.
+    User,
+    /// A plugin directory shared by every user of the system, found in `XDG_DATA_DIRS`.
+    System,
+    /// The default plugins built into the application.
+    BuiltIn,
+}
+
+impl fmt::Display for PluginScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Flag => "--plugin-dir",
+            Self::Environment => super::PLUGIN_PATH_VAR,
+            Self::Project => "project",
+            Self::User => "user",
+            Self::System => "system",
+            Self::BuiltIn => "built in",
+        })
+    }
+}
+
+/// A file bundled with a plugin.
+#[derive(Clone, Debug)]
+pub(crate) struct BundledFile {
+    /// Where the file is, relative to the directory that was listed.
+    pub path:       PathBuf,
+    pub contents:   Vec<u8>,
+    pub executable: bool,
+}
+
+impl fmt::Display for PluginSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuiltIn(path) => write!(f, "built in ({})", path.display()),
+            Self::File(path, scope) => write!(f, "{} ({scope})", path.display()),
+        }
+    }
+}
+
+impl PluginSource {
+    /// The path of the plugin file. Paths of built-in plugins are relative to the
+    /// `default-plugins` directory.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::BuiltIn(path) | Self::File(path, _) => path,
+        }
+    }
+
+    /// The kind of place the plugin was loaded from.
+    pub fn scope(&self) -> PluginScope {
+        match self {
+            Self::BuiltIn(_) => PluginScope::BuiltIn,
+            Self::File(_, scope) => *scope,
+        }
+    }
+
+    /// The directory the plugin is in, which files bundled with it are relative to.
+    fn dir(&self) -> &Path {
+        match self {
+            Self::BuiltIn(path) | Self::File(path, _) => path.parent().unwrap_or(Path::new("")),
+        }
+    }
+
+    /// Reads the file at `path`, relative to the directory of the plugin.
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = self.dir().join(path);
+
+        match self {
+            Self::BuiltIn(_) => crate::consts::DEFAULT_PLUGIN_FILES
+                .iter()
+                .find(|(file, ..)| Path::new(file) == path)
+                .map(|(_, contents, _)| contents.to_vec())
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound)),
+            Self::File(..) => fs::read(path),
+        }
+    }
+
+    /// Lists every file in the directory at `path`, relative to the directory of the
+    /// plugin, and its sub-directories. Returns an empty list if there is no such
+    /// directory.
+    pub(crate) fn files(&self, path: &Path) -> io::Result<Vec<BundledFile>> {
+        let root = self.dir().join(path);
+
+        match self {
+            Self::BuiltIn(_) => Ok(crate::consts::DEFAULT_PLUGIN_FILES
+                .iter()
+                .filter_map(|(file, contents, executable)| {
+                    Some(BundledFile {
+                        path:       Path::new(file).strip_prefix(&root).ok()?.to_owned(),
+                        contents:   contents.to_vec(),
+                        executable: *executable,
+                    })
+                })
+                .collect()),
+            Self::File(..) => {
+                let mut files = Vec::new();
+
+                if root.is_dir() {
+                    list_files(&root, &root, &mut files)?;
+                }
+
+                Ok(files)
+            },
+        }
+    }
+}
+
+/// Adds every file in `dir` and its sub-directories to `files`, with paths relative to
+/// `root`.
+fn list_files(root: &Path, dir: &Path, files: &mut Vec<BundledFile>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            list_files(root, &path, files)?;
+            continue;
+        }
+
+        files.push(BundledFile {
+            path:       path.strip_prefix(root).unwrap_or(&path).to_owned(),
+            contents:   fs::read(&path)?,
+            executable: is_executable(&path),
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is a file that can be run. Outside of unix, every file can be.
+#[cfg(unix)]
+pub(super) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(super) fn is_executable(path: &Path) -> bool { path.is_file() }
+
+/// Marks the file at `path` as executable by everyone who can read it.
+#[cfg(unix)]
+pub(crate) fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | (permissions.mode() & 0o444) >> 2);
+
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn make_executable(_path: &Path) -> io::Result<()> { Ok(()) }
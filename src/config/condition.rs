@@ -0,0 +1,150 @@
+use super::{ProjectType, RunnerError, Variables};
+
+/// A condition that decides whether a [`super::CommandStep`] is run, given in a plugin
+/// with the `when` field of a step. Conditions are checked against the answers given so
+/// far.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Condition {
+    /// The user chose the given project type.
+    ProjectType(ProjectType),
+    /// The variable with the given name has the given value, compared as text, so that a
+    /// confirmation is `"true"` or `"false"`.
+    Equals(String, String),
+    /// A MiniJinja expression, such as `project_name != 'example'`, that is truthy.
+    Expression(String),
+    /// Every one of the conditions hold.
+    All(Vec<Condition>),
+    /// At least one of the conditions hold.
+    Any(Vec<Condition>),
+    /// The condition does not hold.
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub(crate) fn evaluate(&self, variables: &Variables) -> std::result::Result<bool, RunnerError> {
+        let holds = match self {
+            Self::ProjectType(project_type) =>
+                variables.get("project_type") == Some(project_type.to_string().as_str()),
+            Self::Equals(name, value) => variables.get_text(name).as_ref() == Some(value),
+            Self::Expression(expression) => variables.evaluate(expression)?,
+            Self::All(conditions) => {
+                for condition in conditions {
+                    if !condition.evaluate(variables)? {
+                        return Ok(false);
+                    }
+                }
+
+                true
+            },
+            Self::Any(conditions) => {
+                for condition in conditions {
+                    if condition.evaluate(variables)? {
+                        return Ok(true);
+                    }
+                }
+
+                false
+            },
+            Self::Not(condition) => !condition.evaluate(variables)?,
+        };
+
+        Ok(holds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equals(name: &str, value: &str) -> Condition {
+        Condition::Equals(name.to_owned(), value.to_owned())
+    }
+
+    #[test]
+    fn equals_compares_strings() {
+        let mut variables = Variables::default();
+        variables.set("runtime", "tokio");
+
+        assert!(equals("runtime", "tokio").evaluate(&variables).unwrap());
+        assert!(!equals("runtime", "async-std").evaluate(&variables).unwrap());
+        assert!(!equals("missing", "tokio").evaluate(&variables).unwrap());
+    }
+
+    #[test]
+    fn equals_compares_confirmations_as_rendered() {
+        let mut variables = Variables::default();
+        variables.set("docker", true);
+        variables.set("ci", false);
+
+        assert!(equals("docker", "true").evaluate(&variables).unwrap());
+        assert!(!equals("docker", "false").evaluate(&variables).unwrap());
+        assert!(equals("ci", "false").evaluate(&variables).unwrap());
+    }
+
+    #[test]
+    fn equals_compares_lists_as_rendered() {
+        let mut variables = Variables::default();
+        variables.set("features", vec!["serde", "tokio"]);
+
+        assert!(equals("features", r#"["serde", "tokio"]"#)
+            .evaluate(&variables)
+            .unwrap());
+    }
+
+    #[test]
+    fn project_type_compares_the_chosen_type() {
+        let mut variables = Variables::default();
+
+        assert!(!Condition::ProjectType(ProjectType::Library)
+            .evaluate(&variables)
+            .unwrap());
+
+        variables.set("project_type", ProjectType::Library.to_string());
+
+        assert!(Condition::ProjectType(ProjectType::Library)
+            .evaluate(&variables)
+            .unwrap());
+        assert!(!Condition::ProjectType(ProjectType::Binary)
+            .evaluate(&variables)
+            .unwrap());
+    }
+
+    #[test]
+    fn combines_conditions() {
+        let mut variables = Variables::default();
+        variables.set("runtime", "tokio");
+
+        let holds = || equals("runtime", "tokio");
+        let fails = || equals("runtime", "smol");
+
+        assert!(Condition::All(vec![holds(), holds()])
+            .evaluate(&variables)
+            .unwrap());
+        assert!(!Condition::All(vec![holds(), fails()])
+            .evaluate(&variables)
+            .unwrap());
+        assert!(Condition::All(Vec::new()).evaluate(&variables).unwrap());
+        assert!(Condition::Any(vec![fails(), holds()])
+            .evaluate(&variables)
+            .unwrap());
+        assert!(!Condition::Any(Vec::new()).evaluate(&variables).unwrap());
+        assert!(Condition::Not(Box::new(fails()))
+            .evaluate(&variables)
+            .unwrap());
+    }
+
+    #[test]
+    fn expressions_fail_on_undefined_variables() {
+        let mut variables = Variables::default();
+        variables.set("project_name", "demo");
+
+        assert!(
+            Condition::Expression(String::from("project_name != 'example'"))
+                .evaluate(&variables)
+                .unwrap()
+        );
+        assert!(Condition::Expression(String::from("missing == 'x'"))
+            .evaluate(&variables)
+            .is_err());
+    }
+}
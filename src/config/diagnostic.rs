@@ -0,0 +1,167 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Why a plugin file couldn't be loaded, with where in the file the problem is and, where
+/// it can be worked out, how to fix it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PluginDiagnostic {
+    path:       PathBuf,
+    /// The line and column of the problem, both starting from 1.
+    position:   Option<(usize, usize)>,
+    message:    String,
+    /// The fields or variants that could have been given where the problem is.
+    expected:   Vec<String>,
+    suggestion: Option<String>,
+}
+
+impl PluginDiagnostic {
+    pub fn path(&self) -> &Path { &self.path }
+    pub fn position(&self) -> Option<(usize, usize)> { self.position }
+    pub fn message(&self) -> &str { &self.message }
+    pub fn expected(&self) -> &[String] { &self.expected }
+    pub fn suggestion(&self) -> Option<&str> { self.suggestion.as_deref() }
+
+    /// A problem with the file at `path` that isn't at any particular place in it, such
+    /// as it not being readable.
+    pub(crate) fn new(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            path:       path.to_owned(),
+            position:   None,
+            message:    message.into(),
+            expected:   Vec::new(),
+            suggestion: None,
+        }
+    }
+
+    /// Adds a suggestion for how to fix the problem, if there is one.
+    pub(crate) fn with_suggestion(mut self, suggestion: Option<String>) -> Self {
+        self.suggestion = suggestion;
+        self
+    }
+
+    pub(crate) fn from_io(path: &Path, error: &io::Error) -> Self {
+        Self::new(path, format!("could not read the plugin: {error}"))
+    }
+
+    /// Describes the error from parsing the plugin at `path`, suggesting the nearest
+    /// field or variant when one was misspelled.
+    pub(crate) fn from_ron(path: &Path, error: &ron::error::SpannedError) -> Self {
+        use ron::Error;
+
+        let in_outer = |outer: &Option<String>| {
+            outer
+                .as_ref()
+                .map(|outer| format!(" in `{outer}`"))
+                .unwrap_or_default()
+        };
+
+        let (message, expected, suggestion) = match &error.code {
+            Error::NoSuchStructField {
+                expected,
+                found,
+                outer,
+            } => (
+                format!("unknown field `{found}`{}", in_outer(outer)),
+                expected.to_vec(),
+                nearest(found, expected).map(|field| format!("did you mean `{field}`?")),
+            ),
+            Error::NoSuchEnumVariant {
+                expected,
+                found,
+                outer,
+            } => (
+                format!("unknown variant `{found}`{}", in_outer(outer)),
+                expected.to_vec(),
+                nearest(found, expected).map(|variant| format!("did you mean `{variant}`?")),
+            ),
+            Error::MissingStructField { field, outer } => (
+                format!("missing field `{field}`{}", in_outer(outer)),
+                vec![*field],
+                Some(format!("add `{field}: ...`{}", in_outer(outer))),
+            ),
+            Error::DuplicateStructField { field, outer } => (
+                format!("the field `{field}` is given twice{}", in_outer(outer)),
+                Vec::new(),
+                Some(String::from("remove one of them")),
+            ),
+            Error::ExpectedComma => (
+                error.code.to_string(),
+                Vec::new(),
+                Some(String::from("add a comma after the previous item")),
+            ),
+            Error::Eof => (
+                String::from("the plugin ended unexpectedly"),
+                Vec::new(),
+                Some(String::from(
+                    "check for a bracket or a string that isn't closed",
+                )),
+            ),
+            code => (code.to_string(), Vec::new(), None),
+        };
+
+        Self {
+            path: path.to_owned(),
+            position: Some((error.position.line, error.position.col)),
+            message,
+            expected: expected.into_iter().map(str::to_owned).collect(),
+            suggestion,
+        }
+    }
+}
+
+impl fmt::Display for PluginDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let Some((line, column)) = self.position {
+            write!(f, ":{line}:{column}")?;
+        }
+
+        write!(f, ": {}", self.message)?;
+
+        if !self.expected.is_empty() {
+            write!(f, "\n  expected: {}", self.expected.join(", "))?;
+        }
+
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, "\n  help: {suggestion}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the option that `found` is most likely a misspelling of, if any are close
+/// enough to it.
+pub(crate) fn nearest<'a>(found: &str, options: &[&'a str]) -> Option<&'a str> {
+    let found = found.to_lowercase();
+
+    options
+        .iter()
+        .map(|option| (edit_distance(&found, &option.to_lowercase()), *option))
+        .filter(|(distance, option)| *distance <= (option.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option)
+}
+
+/// The number of characters that have to be inserted, removed or replaced to turn `a`
+/// into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
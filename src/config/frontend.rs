@@ -0,0 +1,47 @@
+use std::collections::BTreeSet;
+
+use super::{RunningConfigMessage, Validation};
+
+/// How a run talks to the person running it. The runner asks its questions and reports
+/// its progress through a frontend, such as the TUI, or standard input and output when
+/// `proman` is run without the TUI.
+///
+/// The methods are called on the thread the run is on, and the questions block until
+/// they have been answered. A question that returns [`None`] wasn't answered, which
+/// cancels the run.
+pub trait Frontend {
+    /// Asks for a line of text. An empty answer is replaced with `default`, if there is
+    /// one, and the answer should be asked for again until it passes `validation`.
+    fn ask_text(
+        &mut self,
+        label: &str,
+        default: Option<&str>,
+        validation: &Validation,
+    ) -> Option<String>;
+
+    /// Asks for one of `options`, returning the index of the option that was chosen. The
+    /// option at `default` should be chosen to begin with.
+    fn ask_choice(&mut self, label: &str, options: &[String], default: usize) -> Option<usize>;
+
+    /// Asks a yes or no question. By default, this is asked as a choice between "Yes" and
+    /// "No".
+    fn ask_confirm(&mut self, label: &str, default: bool) -> Option<bool> {
+        let options = [String::from("Yes"), String::from("No")];
+
+        self.ask_choice(label, &options, usize::from(!default))
+            .map(|index| index == 0)
+    }
+
+    /// Asks for any number of `options`, returning the indexes of those that were chosen.
+    /// The options at `default` should be chosen to begin with.
+    fn ask_multi_select(
+        &mut self,
+        label: &str,
+        options: &[String],
+        default: &BTreeSet<usize>,
+    ) -> Option<BTreeSet<usize>>;
+
+    /// Shows the progress of the run, such as the step being run or the output of a
+    /// command. The last message of every run is [`RunningConfigMessage::Finished`].
+    fn show(&mut self, message: RunningConfigMessage);
+}
@@ -0,0 +1,479 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Files larger than this aren't backed up by a [`Journal`], so changes made to them
+/// can't be undone.
+const MAX_BACKUP_SIZE: u64 = 4 * 1024 * 1024;
+
+/// The most a [`Journal`] backs up in total, so that watching a large directory doesn't
+/// hold all of it in memory. Files seen once this is used up aren't backed up either.
+const MAX_TOTAL_BACKUP_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A change made to the filesystem during a run. Changes to files that weren't backed up
+/// are found, but aren't `restorable`, so they can't be undone.
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum Effect {
+    Created(PathBuf),
+    Modified {
+        path:       PathBuf,
+        restorable: bool,
+    },
+    Removed {
+        path:       PathBuf,
+        restorable: bool,
+    },
+}
+
+impl Effect {
+    /// Whether the effect can be undone.
+    pub fn is_restorable(&self) -> bool {
+        match self {
+            Self::Created(_) => true,
+            Self::Modified { restorable, .. } | Self::Removed { restorable, .. } => *restorable,
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not_restorable = |restorable: &bool| if *restorable { "" } else { " (not restorable)" };
+
+        match self {
+            Self::Created(path) => write!(f, "created {}", path.display()),
+            Self::Modified { path, restorable } => write!(
+                f,
+                "modified {}{}",
+                path.display(),
+                not_restorable(restorable)
+            ),
+            Self::Removed { path, restorable } => write!(
+                f,
+                "removed {}{}",
+                path.display(),
+                not_restorable(restorable)
+            ),
+        }
+    }
+}
+
+/// The state of a single path when it was first seen by a [`Journal`].
+#[derive(Clone, Debug)]
+enum Entry {
+    Directory,
+    File(Backup),
+}
+
+/// What a [`Journal`] keeps of a file to find out whether it was changed.
+#[derive(Clone, Debug)]
+enum Backup {
+    /// The contents of the file, which it can be restored to.
+    Contents(Vec<u8>),
+    /// The size and modification time of a file that wasn't backed up, which are enough
+    /// to notice most changes to it.
+    Stamp {
+        len:      u64,
+        modified: Option<SystemTime>,
+    },
+}
+
+impl Backup {
+    /// The stamp of the file at `path` as it is now.
+    fn stamp(path: &Path) -> Self {
+        let (len, modified) = stamp(path);
+
+        Self::Stamp { len, modified }
+    }
+
+    fn is_restorable(&self) -> bool { matches!(self, Self::Contents(_)) }
+
+    /// Whether the file at `path` is different from when it was backed up.
+    fn is_changed(&self, path: &Path) -> bool {
+        match self {
+            Self::Contents(contents) => fs::read(path).ok().as_ref() != Some(contents),
+            Self::Stamp { len, modified } => stamp(path) != (*len, *modified),
+        }
+    }
+}
+
+/// Keeps track of the filesystem effects of a run, so that they can be undone if the run
+/// is stopped before it finishes. The journal backs up the files in the working directory
+/// (and the project directory, if it already existed) before any step touches them, and
+/// works out what was modified or removed since by comparing against that. Only the paths
+/// that the run says it creates are taken to be created by it, along with everything in
+/// the directories among them, so that files made by anything else while the run is in
+/// progress are never cleaned up.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Journal {
+    /// The directories being watched, and whether their sub-directories are also watched.
+    watched:   Vec<(PathBuf, bool)>,
+    entries:   BTreeMap<PathBuf, Entry>,
+    /// The paths that didn't exist before the run created them. None of them are inside
+    /// another.
+    created:   BTreeSet<PathBuf>,
+    /// How many bytes of files have been backed up, up to [`MAX_TOTAL_BACKUP_SIZE`].
+    backed_up: u64,
+}
+
+impl Journal {
+    /// Creates a journal watching the top level of `working_dir`.
+    pub fn new(working_dir: &Path) -> Self {
+        let mut journal = Self::default();
+        journal.watch(working_dir, false);
+
+        journal
+    }
+
+    /// Starts watching `dir`, including its sub-directories if `recursive` is set,
+    /// backing up the files in it. If the directory doesn't exist yet, or the run
+    /// created it, then there is nothing to back up.
+    pub fn watch(&mut self, dir: &Path, recursive: bool) {
+        if !dir.is_dir()
+            || self.watched.iter().any(|(watched, _)| watched == dir)
+            || self.is_created(dir)
+        {
+            return;
+        }
+
+        self.watched.push((dir.to_owned(), recursive));
+
+        let mut paths = Vec::new();
+        walk(dir, recursive, &mut |path| {
+            paths.push(path.to_owned());
+            true
+        });
+
+        for path in paths {
+            if self.entries.contains_key(&path) {
+                continue;
+            }
+
+            let entry = if path.is_dir() {
+                Entry::Directory
+            } else {
+                Entry::File(self.backup(&path))
+            };
+
+            self.entries.insert(path, entry);
+        }
+    }
+
+    /// Records that the run is about to create `path`, either by writing a file there or
+    /// by creating a directory that its steps fill in, such as the project directory. If
+    /// the path doesn't exist yet, it and everything that ends up in it belong to the
+    /// run. An existing file is backed up instead, so that the changes to it can be
+    /// undone, and an existing directory is left to [`Self::watch`].
+    pub fn creating(&mut self, path: &Path) {
+        if self.is_created(path) {
+            return;
+        }
+
+        if path.is_file() {
+            if !self.entries.contains_key(path) {
+                let backup = self.backup(path);
+                self.entries.insert(path.to_owned(), Entry::File(backup));
+            }
+
+            return;
+        }
+
+        if fs::symlink_metadata(path).is_err() {
+            self.created.retain(|created| !created.starts_with(path));
+            self.created.insert(path.to_owned());
+        }
+    }
+
+    /// Reads the contents of the file at `path` so that it can be restored later, unless
+    /// it is too large to reasonably keep in memory, or too much has been backed up
+    /// already. Only the stamp of the file is kept then.
+    fn backup(&mut self, path: &Path) -> Backup {
+        let (len, _) = stamp(path);
+
+        if len > MAX_BACKUP_SIZE || self.backed_up + len > MAX_TOTAL_BACKUP_SIZE {
+            return Backup::stamp(path);
+        }
+
+        match fs::read(path) {
+            Ok(contents) => {
+                self.backed_up += contents.len() as u64;
+                Backup::Contents(contents)
+            },
+            Err(_) => Backup::stamp(path),
+        }
+    }
+
+    /// Whether `path` is, or is inside, a path that the run created.
+    fn is_created(&self, path: &Path) -> bool {
+        path.ancestors()
+            .any(|ancestor| self.created.contains(ancestor))
+    }
+
+    /// Works out every effect of the run so far: the paths it created that still exist,
+    /// and the backed up files and directories that have changed since.
+    pub fn effects(&self) -> Vec<Effect> {
+        let mut effects = self
+            .created
+            .iter()
+            .filter(|path| fs::symlink_metadata(path).is_ok())
+            .map(|path| Effect::Created(path.clone()))
+            .collect::<Vec<_>>();
+
+        for (path, entry) in &self.entries {
+            if fs::symlink_metadata(path).is_err() {
+                effects.push(Effect::Removed {
+                    path:       path.clone(),
+                    restorable: match entry {
+                        Entry::Directory => true,
+                        Entry::File(backup) => backup.is_restorable(),
+                    },
+                });
+            } else if let Entry::File(backup) = entry {
+                if backup.is_changed(path) {
+                    effects.push(Effect::Modified {
+                        path:       path.clone(),
+                        restorable: backup.is_restorable(),
+                    });
+                }
+            }
+        }
+
+        effects.sort();
+        effects
+    }
+
+    /// Undoes every effect of the run, returning what was undone. Created paths are
+    /// deleted, and modified or removed files are restored to their original contents
+    /// where they were backed up. Effects that aren't restorable are left as they are,
+    /// and aren't returned.
+    pub fn rollback(&self) -> io::Result<Vec<Effect>> {
+        let effects = self
+            .effects()
+            .into_iter()
+            .filter(Effect::is_restorable)
+            .collect::<Vec<_>>();
+
+        for effect in &effects {
+            match effect {
+                Effect::Created(path) if path.is_dir() && !path.is_symlink() =>
+                    fs::remove_dir_all(path)?,
+                Effect::Created(path) => fs::remove_file(path)?,
+                Effect::Modified { path, .. } | Effect::Removed { path, .. } =>
+                    match self.entries.get(path) {
+                        Some(Entry::Directory) => fs::create_dir_all(path)?,
+                        Some(Entry::File(Backup::Contents(contents))) => {
+                            if let Some(parent) = path.parent() {
+                                fs::create_dir_all(parent)?;
+                            }
+
+                            fs::write(path, contents)?;
+                        },
+                        _ => (),
+                    },
+            }
+        }
+
+        Ok(effects)
+    }
+}
+
+/// The size and modification time of the file at `path`, or nothing if they can't be
+/// read.
+fn stamp(path: &Path) -> (u64, Option<SystemTime>) {
+    match fs::metadata(path) {
+        Ok(metadata) => (metadata.len(), metadata.modified().ok()),
+        Err(_) => (0, None),
+    }
+}
+
+/// Calls `f` with every path in `dir`. If `recursive` is set, sub-directories are walked
+/// as well, as long as `f` returns `true` for them.
+fn walk<F>(dir: &Path, recursive: bool, f: &mut F)
+where
+    F: FnMut(&Path) -> bool,
+{
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(std::result::Result::ok) {
+        let path = entry.path();
+        let descend = f(&path);
+
+        if recursive && descend && path.is_dir() && !path.is_symlink() {
+            walk(&path, recursive, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    /// An empty directory of its own in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("proman-journal-{}-{name}", process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn records_the_paths_the_run_creates() {
+        let dir = temp_dir("created");
+        let mut journal = Journal::new(&dir);
+
+        let file = dir.join("file.txt");
+        journal.creating(&file);
+        fs::write(&file, "made by the run").unwrap();
+
+        let project = dir.join("project");
+        journal.creating(&project);
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+        journal.creating(&project.join("src/lib.rs"));
+
+        // files made by anything else aren't the run's to clean up.
+        fs::write(dir.join("unrelated.txt"), "made by someone else").unwrap();
+
+        assert_eq!(
+            journal.effects(),
+            [
+                Effect::Created(file.clone()),
+                Effect::Created(project.clone())
+            ]
+        );
+        assert_eq!(journal.rollback().unwrap().len(), 2);
+        assert!(!file.exists());
+        assert!(!project.exists());
+        assert!(dir.join("unrelated.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_out_created_paths_that_are_gone() {
+        let dir = temp_dir("gone");
+        let mut journal = Journal::new(&dir);
+
+        journal.creating(&dir.join("never-made"));
+
+        assert!(journal.effects().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn restores_modified_files() {
+        let dir = temp_dir("modified");
+        let file = dir.join("Cargo.toml");
+        fs::write(&file, "[package]").unwrap();
+
+        let mut journal = Journal::new(&dir);
+        journal.creating(&file);
+        fs::write(&file, "[workspace]").unwrap();
+
+        let modified = Effect::Modified {
+            path:       file.clone(),
+            restorable: true,
+        };
+        assert_eq!(journal.effects(), std::slice::from_ref(&modified));
+        assert_eq!(journal.rollback().unwrap(), [modified]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "[package]");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn restores_removed_files_and_directories() {
+        let dir = temp_dir("removed");
+        fs::create_dir_all(dir.join("project/src")).unwrap();
+        fs::write(dir.join("project/src/main.rs"), "fn main() {}").unwrap();
+
+        let mut journal = Journal::new(&dir);
+        journal.watch(&dir.join("project"), true);
+        fs::remove_dir_all(dir.join("project/src")).unwrap();
+
+        assert_eq!(
+            journal.effects(),
+            [
+                Effect::Removed {
+                    path:       dir.join("project/src"),
+                    restorable: true,
+                },
+                Effect::Removed {
+                    path:       dir.join("project/src/main.rs"),
+                    restorable: true,
+                },
+            ]
+        );
+
+        journal.rollback().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("project/src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert!(journal.effects().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_back_up_large_files() {
+        let dir = temp_dir("large");
+        let large = dir.join("large.bin");
+        let contents = vec![0; MAX_BACKUP_SIZE as usize + 1];
+        fs::write(&large, &contents).unwrap();
+
+        let journal = Journal::new(&dir);
+        fs::write(&large, "smaller").unwrap();
+
+        assert_eq!(
+            journal.effects(),
+            [Effect::Modified {
+                path:       large.clone(),
+                restorable: false,
+            }]
+        );
+        assert!(journal.rollback().unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&large).unwrap(), "smaller");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stops_backing_up_files_past_the_total() {
+        let dir = temp_dir("total");
+        let contents = vec![0; MAX_BACKUP_SIZE as usize];
+        let count = MAX_TOTAL_BACKUP_SIZE / MAX_BACKUP_SIZE + 1;
+
+        for index in 0..count {
+            fs::write(dir.join(format!("{index}.bin")), &contents).unwrap();
+        }
+
+        let journal = Journal::new(&dir);
+        assert_eq!(journal.backed_up, MAX_TOTAL_BACKUP_SIZE);
+
+        for index in 0..count {
+            fs::write(dir.join(format!("{index}.bin")), "changed").unwrap();
+        }
+
+        let effects = journal.effects();
+        assert_eq!(effects.len() as u64, count);
+        assert_eq!(
+            effects
+                .iter()
+                .filter(|effect| !effect.is_restorable())
+                .count(),
+            1
+        );
+        assert_eq!(journal.rollback().unwrap().len() as u64, count - 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,15 +1,40 @@
-use std::{fs, io, path::PathBuf};
+use std::{env, ffi::OsString, fs, io, path::PathBuf};
 
 #[cfg(not(debug_assertions))]
 use directories::ProjectDirs;
 
-pub(crate) use parser::*;
+pub use answers::*;
+pub use bundle::*;
+pub use condition::*;
+pub use diagnostic::*;
+pub use frontend::*;
+pub use journal::*;
+pub use parser::*;
+pub use process::{kill_running_commands, CommandEnvironment};
+pub use prompt::*;
+pub use requirement::*;
+pub use runner::*;
+pub use schema::{migrate_plugin_file, Migration, SCHEMA_VERSION};
+pub use validation::*;
+pub(crate) use variables::*;
 
+mod answers;
+mod bundle;
+mod condition;
+mod diagnostic;
+mod frontend;
+mod journal;
 mod parser;
+mod process;
+mod prompt;
+mod requirement;
+mod runner;
+mod schema;
+mod validation;
+mod variables;
 
 #[derive(Debug, thiserror::Error)]
-#[allow(dead_code)]
-pub(crate) enum Error {
+pub enum Error {
     // std errors
     #[error("an std::io::Error occurred: {0}")]
     IO(#[from] io::Error),
@@ -27,6 +52,23 @@ pub(crate) enum Error {
     CouldNotReadDefaultPlugins(String),
     #[error("no configurations found on the filesystem")]
     NoConfigurations,
+    #[error("there is no plugin for the language \"{0}\"")]
+    UnknownLanguage(String),
+    #[error("the plugin for {language} has no prompt for \"{variable}\"")]
+    UnknownVariable { language: String, variable: String },
+    #[error("no project that members can be added to was found in \"{0}\"")]
+    NoProjectFound(PathBuf),
+    #[error("the project in \"{path}\" could be made with any of {languages}")]
+    AmbiguousProject {
+        path:      PathBuf,
+        languages: String,
+    },
+
+    // answers file errors
+    #[error("could not read the answers in \"{path}\": {reason}")]
+    CouldNotReadAnswers { path: PathBuf, reason: String },
+    #[error("could not save the answers to \"{path}\": {reason}")]
+    CouldNotWriteAnswers { path: PathBuf, reason: String },
 
     // runner errors
     #[error("an occurred in the language configuration runner: {0}")]
@@ -35,8 +77,51 @@ pub(crate) enum Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The directory in a project that holds the plugins for that project, which override
+/// the user's plugins.
+pub const PROJECT_PLUGIN_DIR: &str = ".proman/plugins";
+
+/// The environment variable holding extra plugin directories, separated like the
+/// directories in `PATH`. They take precedence over the usual plugin directories.
+pub const PLUGIN_PATH_VAR: &str = "PROMAN_PLUGIN_PATH";
+
+/// The data directories searched when `XDG_DATA_DIRS` isn't set, as given by the XDG Base
+/// Directory Specification.
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// The plugin directories shared by every user of the system, from the highest precedence
+/// to the lowest: `proman/plugins` in each of the directories in `XDG_DATA_DIRS`. They
+/// aren't created if they don't exist.
+pub fn get_system_plugin_dirs() -> Vec<PathBuf> {
+    if !cfg!(unix) {
+        return Vec::new();
+    }
+
+    let data_dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| OsString::from(DEFAULT_DATA_DIRS));
+
+    // relative paths are to be ignored, as the specification only allows absolute ones.
+    env::split_paths(&data_dirs)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join("proman").join("plugins"))
+        .collect()
+}
+
+/// The plugin directories in [`PLUGIN_PATH_VAR`], from the highest precedence to the
+/// lowest. Empty entries are skipped.
+pub fn get_env_plugin_dirs() -> Vec<PathBuf> {
+    env::var_os(PLUGIN_PATH_VAR)
+        .map(|dirs| {
+            env::split_paths(&dirs)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(debug_assertions)]
-pub(crate) fn get_language_plugin_dir() -> Result<PathBuf> {
+pub fn get_language_plugin_dir() -> Result<PathBuf> {
     let path = PathBuf::from("./plugins");
 
     if !path.exists() {
@@ -48,7 +133,7 @@ pub(crate) fn get_language_plugin_dir() -> Result<PathBuf> {
 }
 
 #[cfg(not(debug_assertions))]
-pub(crate) fn get_language_plugin_dir() -> Result<PathBuf> {
+pub fn get_language_plugin_dir() -> Result<PathBuf> {
     let config_dirs = if let Some(project_dirs) = ProjectDirs::from("com", "stboyden", "proman") {
         project_dirs.config_dir().to_owned()
     } else {
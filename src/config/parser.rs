@@ -1,22 +1,151 @@
 use std::{
-    collections::BTreeSet,
-    fmt,
+    collections::{BTreeMap, BTreeSet},
+    env, fmt,
     fs::{self, File},
     io::{BufReader, Read},
-    sync::{mpsc, Arc, Mutex, RwLock},
+    path::{Path, PathBuf},
 };
 
-use bus::{Bus, BusReader};
-use ratatui::prelude::Text;
-
-use super::{get_language_plugin_dir, Error, Result};
+use super::{
+    diagnostic::nearest,
+    get_env_plugin_dirs, get_language_plugin_dir, get_system_plugin_dirs, migrate_plugin_file,
+    schema::{self, PluginHeader},
+    Condition, Error, LanguageConfigRunner, Migration, PluginDiagnostic, PluginScope, PluginSource,
+    Requirement, Result, Tool, Validation, PROJECT_PLUGIN_DIR,
+};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord)]
-pub(crate) enum CommandType {
+pub enum CommandType {
     PromptProjectType,
     PromptProjectName,
     #[serde(rename = "ShellCommand")]
     Command(String, String),
+    /// Renders `template` and writes it to `path`, which is relative to the project
+    /// directory unless the step has a `cwd`.
+    WriteFile {
+        path:     String,
+        template: Template,
+    },
+    /// Copies the `template/` directory of a plugin bundle into the project directory,
+    /// or the step's `cwd`, rendering the names and contents of the files in it.
+    CopyTemplate,
+    /// Asks for a line of text, stored as a string in the variable called `variable`.
+    PromptText {
+        variable: String,
+        label:    String,
+        #[serde(default)]
+        default:  Option<String>,
+    },
+    /// Asks for one of `options`, stored as a string in the variable called `variable`.
+    /// The first option is the default if none is given.
+    PromptChoice {
+        variable: String,
+        label:    String,
+        options:  Vec<String>,
+        #[serde(default)]
+        default:  Option<String>,
+    },
+    /// Asks a yes or no question, stored as a boolean in the variable called `variable`.
+    PromptConfirm {
+        variable: String,
+        label:    String,
+        #[serde(default)]
+        default:  bool,
+    },
+    /// Asks for any number of `options`, stored as a list in the variable called
+    /// `variable`.
+    PromptMultiSelect {
+        variable: String,
+        label:    String,
+        options:  Vec<String>,
+        #[serde(default)]
+        default:  Vec<String>,
+    },
+    /// Asks for the members of a workspace one at a time, running the plugin's
+    /// `member_steps` for each. The answers are stored in the `members` variable, as a
+    /// list of the names of the members.
+    CreateMembers,
+}
+
+/// A change to the steps of the plugin that a plugin extends, given in the `step_changes`
+/// of the plugin. Steps are found by their `id`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub enum StepChange {
+    /// Adds steps before the first step.
+    Prepend(Vec<CommandStep>),
+    /// Adds steps after the last step, before the plugin's own `command_steps`.
+    Append(Vec<CommandStep>),
+    /// Adds steps before the step with the given id.
+    InsertBefore(String, Vec<CommandStep>),
+    /// Adds steps after the step with the given id.
+    InsertAfter(String, Vec<CommandStep>),
+    /// Replaces the step with the given id.
+    Replace(String, Box<CommandStep>),
+    /// Removes the step with the given id.
+    Remove(String),
+}
+
+impl StepChange {
+    /// Makes the change to `steps`, failing if there's no step with the id it changes.
+    fn apply(&self, steps: &mut Vec<CommandStep>) -> std::result::Result<(), String> {
+        let position = |id: &str| {
+            steps
+                .iter()
+                .position(|step| step.id() == Some(id))
+                .ok_or_else(|| format!("there is no step with the id \"{id}\" to change"))
+        };
+
+        match self {
+            Self::Prepend(new) => {
+                steps.splice(0..0, new.iter().cloned());
+            },
+            Self::Append(new) => steps.extend(new.iter().cloned()),
+            Self::InsertBefore(id, new) => {
+                let index = position(id)?;
+                steps.splice(index..index, new.iter().cloned());
+            },
+            Self::InsertAfter(id, new) => {
+                let index = position(id)? + 1;
+                steps.splice(index..index, new.iter().cloned());
+            },
+            Self::Replace(id, step) => {
+                let index = position(id)?;
+                steps[index] = (**step).clone();
+            },
+            Self::Remove(id) => {
+                let index = position(id)?;
+                steps.remove(index);
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Where the template of a [`CommandType::WriteFile`] step comes from.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Template {
+    /// The template is given in the plugin itself.
+    Inline(String),
+    /// The template is in a file, relative to the directory of the plugin.
+    File(String),
+}
+
+impl CommandType {
+    /// The variable that the answer to a prompt step is stored in, which is also the name
+    /// it's answered by before a run. Steps that aren't prompts don't have one.
+    pub fn variable(&self) -> Option<&str> {
+        match self {
+            Self::PromptProjectName => Some("project_name"),
+            Self::PromptProjectType => Some("project_type"),
+            Self::PromptText { variable, .. }
+            | Self::PromptChoice { variable, .. }
+            | Self::PromptConfirm { variable, .. }
+            | Self::PromptMultiSelect { variable, .. } => Some(variable),
+            Self::CreateMembers => Some("members"),
+            Self::Command(..) | Self::WriteFile { .. } | Self::CopyTemplate => None,
+        }
+    }
 }
 
 impl fmt::Display for CommandType {
@@ -26,20 +155,77 @@ impl fmt::Display for CommandType {
             Self::PromptProjectName => f.write_fmt(format_args!("Prompting project name")),
             Self::Command(command, arguments) =>
                 f.write_fmt(format_args!("Running \"{command} {arguments}\"...")),
+            Self::WriteFile { path, .. } => f.write_fmt(format_args!("Writing \"{path}\"...")),
+            Self::CopyTemplate => f.write_str("Copying the template..."),
+            Self::CreateMembers => f.write_str("Creating the members of the workspace..."),
+            Self::PromptText { label, .. }
+            | Self::PromptChoice { label, .. }
+            | Self::PromptConfirm { label, .. }
+            | Self::PromptMultiSelect { label, .. } =>
+                f.write_fmt(format_args!("Prompting \"{label}\"")),
         }
     }
 }
 
+/// What the runner should do when a step fails, given in a plugin with the `on_failure`
+/// field of a step.
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord, Default,
+)]
+pub enum FailurePolicy {
+    /// Stop the run.
+    #[default]
+    Abort,
+    /// Run the step again, up to the given number of times, before stopping the run.
+    Retry(u32),
+    /// Carry on with the next step.
+    Skip,
+    /// Ask the user whether to retry, skip or abort.
+    Ask,
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, Eq, Ord)]
-#[serde(rename = "Step")]
-pub(crate) struct CommandStep {
-    name:    String,
-    command: CommandType,
+#[serde(rename = "Step", deny_unknown_fields)]
+pub struct CommandStep {
+    name:       String,
+    /// What the step is called by plugins that extend this one, to change it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id:         Option<String>,
+    command:    CommandType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    when:       Option<Condition>,
+    #[serde(default)]
+    on_failure: FailurePolicy,
+    /// The directory the step is run in, relative to the directory `proman` was run in.
+    /// Defaults to the directory `proman` was run in, and is created if it doesn't exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd:        Option<String>,
+    /// Environment variables that are set for this step only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env:        BTreeMap<String, String>,
+    /// How many seconds the step can run for before it's killed and treated as failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout:    Option<u64>,
+    /// Rules that the answer has to follow, for steps that prompt for text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    validation: Option<Validation>,
+    /// Where the plugin the step was inherited from was loaded from, which the files the
+    /// step uses are read from. Steps of the plugin itself use the plugin's source.
+    #[serde(skip)]
+    source:     Option<PluginSource>,
 }
 
 impl CommandStep {
     pub fn name(&self) -> &str { &self.name }
-    pub fn command_string(&self) -> String { self.command.to_string() }
+    pub fn id(&self) -> Option<&str> { self.id.as_deref() }
+    pub fn command(&self) -> &CommandType { &self.command }
+    pub fn when(&self) -> Option<&Condition> { self.when.as_ref() }
+    pub fn on_failure(&self) -> &FailurePolicy { &self.on_failure }
+    pub fn cwd(&self) -> Option<&str> { self.cwd.as_deref() }
+    pub fn env(&self) -> &BTreeMap<String, String> { &self.env }
+    pub fn timeout(&self) -> Option<u64> { self.timeout }
+    pub fn validation(&self) -> Option<&Validation> { self.validation.as_ref() }
+    pub fn source(&self) -> Option<&PluginSource> { self.source.as_ref() }
 }
 
 #[derive(
@@ -52,52 +238,223 @@ pub enum ProjectType {
     Workspace,
 }
 
-impl<'a> From<ProjectType> for Text<'a> {
-    fn from(project_type: ProjectType) -> Text<'a> {
-        let s = match project_type {
-            ProjectType::Binary => "Binary",
-            ProjectType::Library => "Library",
-            ProjectType::Workspace => "Workspace",
-        };
+impl fmt::Display for ProjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Binary => "Binary",
+            Self::Library => "Library",
+            Self::Workspace => "Workspace",
+        })
+    }
+}
+
+/// A file that marks a directory as the root of a project made with a plugin, such as the
+/// `Cargo.toml` of a Cargo workspace.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+pub struct ProjectMarker {
+    /// The name of the file, relative to the root of the project.
+    file:     String,
+    /// Text that the file has to contain, such as `[workspace]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contains: Option<String>,
+}
+
+impl ProjectMarker {
+    /// Whether `dir` holds the file of the marker, with the text it has to contain.
+    pub fn matches(&self, dir: &Path) -> bool {
+        let path = dir.join(&self.file);
 
-        Text::from(s)
+        match self.contains {
+            Some(ref contains) =>
+                fs::read_to_string(path).is_ok_and(|contents| contents.contains(contains.as_str())),
+            None => path.is_file(),
+        }
     }
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq)]
-pub(crate) struct LanguageConfig {
-    language:      String,
-    requirements:  Vec<String>,
-    project_types: BTreeSet<ProjectType>,
-    command_steps: Vec<CommandStep>,
+#[serde(deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// The version of the plugin format the plugin is written for, see
+    /// [`super::SCHEMA_VERSION`]. Plugins without one are written for version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema_version:     Option<u32>,
+    /// The oldest version of `proman` that can load the plugin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_proman_version: Option<String>,
+    language:           String,
+    /// The language of the plugin this one is based on. The fields of this plugin are
+    /// merged into those of that plugin when the plugins are loaded, see
+    /// [`LanguageConfig::inherit`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extends:            Option<String>,
+    #[serde(default)]
+    requirements:       Vec<Requirement>,
+    /// How to check the version of, and install, the tools named in `requirements`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tools:              BTreeMap<String, Tool>,
+    #[serde(default)]
+    project_types:      BTreeSet<ProjectType>,
+    #[serde(default)]
+    command_steps:      Vec<CommandStep>,
+    /// Changes to the steps of the plugin this one extends.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    step_changes:       Vec<StepChange>,
+    /// The steps run for each member of a workspace, by a
+    /// [`CommandType::CreateMembers`] step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    member_steps:       Vec<CommandStep>,
+    /// The files that mark a directory as a project made with this plugin, which members
+    /// can be added to with the `add_member` steps.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    detect:             Vec<ProjectMarker>,
+    /// The steps run for each member added to an existing project, which should also add
+    /// the member to the manifests of the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    add_member:         Vec<CommandStep>,
+    /// Environment variables that are set for every step, unless overridden by the step.
+    /// Like `path`, they're used to find the required tools before any prompts are asked,
+    /// so they can only use the built-in variables.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env:                BTreeMap<String, String>,
+    /// Directories that are added to the front of `PATH` for every step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    path:               Vec<String>,
+    /// Where the plugin was loaded from, which files bundled with it are read from.
+    #[serde(skip)]
+    source:             Option<PluginSource>,
 }
 
 impl LanguageConfig {
+    pub fn schema_version(&self) -> u32 { self.schema_version.unwrap_or(1) }
+    pub(super) fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = Some(version);
+    }
+    pub fn min_proman_version(&self) -> Option<&str> { self.min_proman_version.as_deref() }
     pub fn language(&self) -> &str { &self.language }
-    pub fn requirements(&self) -> &[String] { &self.requirements }
+    pub fn extends(&self) -> Option<&str> { self.extends.as_deref() }
+    pub fn requirements(&self) -> &[Requirement] { &self.requirements }
+    pub fn tools(&self) -> &BTreeMap<String, Tool> { &self.tools }
+    pub fn project_types(&self) -> &BTreeSet<ProjectType> { &self.project_types }
     pub fn command_steps(&self) -> &[CommandStep] { &self.command_steps }
+    pub fn step_changes(&self) -> &[StepChange] { &self.step_changes }
+    pub fn member_steps(&self) -> &[CommandStep] { &self.member_steps }
+    pub fn detect(&self) -> &[ProjectMarker] { &self.detect }
+    pub fn add_member(&self) -> &[CommandStep] { &self.add_member }
+    pub fn env(&self) -> &BTreeMap<String, String> { &self.env }
+    pub fn path(&self) -> &[String] { &self.path }
+    pub fn source(&self) -> Option<&PluginSource> { self.source.as_ref() }
+
+    pub fn create_runner(&self) -> LanguageConfigRunner { LanguageConfigRunner::new(self.clone()) }
+
+    /// Merges this plugin into `parent`, the plugin it extends. The `step_changes` are
+    /// made to the steps of the parent, and then the plugin's own `command_steps` are
+    /// added after them. The requirements, project types, tools, environment variables
+    /// and `path` of both plugins are combined, with this plugin's taking precedence. The
+    /// member steps, `detect` markers and `add_member` steps are the parent's, unless
+    /// this plugin has its own. The steps taken from the parent keep the parent's source,
+    /// so that their templates are still found. Returns why the steps can't be changed if
+    /// they can't.
+    pub fn inherit(&self, parent: &LanguageConfig) -> std::result::Result<Self, String> {
+        // steps the parent inherited itself already have the source of their own plugin.
+        let inherited = |steps: &[CommandStep]| {
+            steps
+                .iter()
+                .cloned()
+                .map(|mut step| {
+                    step.source = step.source.or_else(|| parent.source.clone());
+                    step
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut steps = inherited(&parent.command_steps);
+
+        for change in &self.step_changes {
+            change.apply(&mut steps)?;
+        }
+        steps.extend(self.command_steps.iter().cloned());
+
+        let mut requirements = parent.requirements.clone();
+        for requirement in &self.requirements {
+            if !requirements.contains(requirement) {
+                requirements.push(requirement.clone());
+            }
+        }
+
+        let mut tools = parent.tools.clone();
+        tools.extend(self.tools.clone());
+        let mut env = parent.env.clone();
+        env.extend(self.env.clone());
+        let either = |own: &[CommandStep], parent: &[CommandStep]| {
+            if own.is_empty() {
+                inherited(parent)
+            } else {
+                own.to_vec()
+            }
+        };
 
-    pub fn create_runner(&self) -> LanguageConfigRunner {
-        LanguageConfigRunner::new(self.command_steps.clone(), self.project_types.clone())
+        Ok(Self {
+            schema_version: self.schema_version,
+            min_proman_version: self.min_proman_version.clone(),
+            language: self.language.clone(),
+            extends: self.extends.clone(),
+            requirements,
+            tools,
+            project_types: parent
+                .project_types
+                .union(&self.project_types)
+                .cloned()
+                .collect(),
+            command_steps: steps,
+            step_changes: Vec::new(),
+            member_steps: either(&self.member_steps, &parent.member_steps),
+            detect: if self.detect.is_empty() {
+                parent.detect.clone()
+            } else {
+                self.detect.clone()
+            },
+            add_member: either(&self.add_member, &parent.add_member),
+            env,
+            path: [self.path.clone(), parent.path.clone()].concat(),
+            source: self.source.clone(),
+        })
+    }
+
+    /// Finds the root of the project that `dir` is in, if it's a project made with this
+    /// plugin, by looking for the files of the plugin's `detect` markers in `dir` and the
+    /// directories containing it.
+    pub fn find_project(&self, dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .find(|dir| self.detect.iter().any(|marker| marker.matches(dir)))
+            .map(Path::to_path_buf)
     }
 }
 
-impl<'a> From<LanguageConfig> for Text<'a> {
-    fn from(value: LanguageConfig) -> Self { Text::raw(value.language.clone()) }
+/// Parses a [`LanguageConfig`] from the contents of a plugin file. Optional fields, such
+/// as the `when` of a step, can be given without wrapping them in `Some(...)`.
+pub(crate) fn parse_language_config(
+    contents: &str,
+) -> std::result::Result<LanguageConfig, ron::error::SpannedError> {
+    ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+        .from_str(contents)
 }
 
-/// Parses the default language configurations from
-/// [`crate::consts::DEFAULT_PLUGINS_BYTES`] which is configured and set at compile-time
-/// in the build script. Returns a [`Result<BTreeSet<LanguageConfig>>`] but should not
-/// error.
+/// Parses the default language configurations from [`crate::consts::DEFAULT_PLUGINS`]
+/// which is configured and set at compile-time in the build script. Returns a
+/// [`Result<BTreeSet<LanguageConfig>>`] but should not error.
 fn parse_default_language_configs() -> Result<BTreeSet<LanguageConfig>> {
     let mut language_configurations = BTreeSet::new();
 
-    for bytes in crate::consts::DEFAULT_PLUGINS_BYTES {
+    for (path, bytes) in crate::consts::DEFAULT_PLUGINS {
         let contents = String::from_utf8(Vec::from(bytes))?;
 
-        match ron::from_str::<LanguageConfig>(&contents) {
-            Ok(config) => language_configurations.insert(config),
+        match parse_language_config(&contents) {
+            Ok(mut config) => {
+                config.source = Some(PluginSource::BuiltIn(PathBuf::from(path)));
+                language_configurations.insert(config)
+            },
             Err(error) => return Err(Error::CouldNotReadDefaultPlugins(error.to_string())),
         };
     }
@@ -105,38 +462,450 @@ fn parse_default_language_configs() -> Result<BTreeSet<LanguageConfig>> {
     Ok(language_configurations)
 }
 
-/// Parse the plugins in the plugins directory, specified at runtime and return the
-/// available language configurations that could be parsed.
-pub(crate) fn parse_language_configs() -> Result<BTreeSet<LanguageConfig>> {
-    let plugin_dir = get_language_plugin_dir()?;
-    let mut language_configurations = parse_default_language_configs()?;
+/// Finds the plugin files in `plugin_dir`. Plugins are either `.ron` files directly in
+/// the directory, or the `.ron` files in a plugin bundle, which is a directory holding a
+/// plugin along with the files it uses. Entries that can't be read, or the directory
+/// itself, are reported in `report`, so that the other directories are still searched.
+fn find_plugin_files(plugin_dir: &Path, report: &mut PluginReport) -> Vec<PathBuf> {
+    let mut plugin_files = Vec::new();
+
+    let entries = match fs::read_dir(plugin_dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            report.add_diagnostic(PluginDiagnostic::new(
+                plugin_dir,
+                format!("could not read the plugin directory: {error}"),
+            ));
+
+            return plugin_files;
+        },
+    };
+
+    for path in entries {
+        let path = match path {
+            Ok(entry) => entry.path(),
+            Err(error) => {
+                report.add_diagnostic(PluginDiagnostic::from_io(plugin_dir, &error));
+                continue;
+            },
+        };
 
-    for path in fs::read_dir(plugin_dir)? {
-        if path.is_err() {
+        if !path.is_dir() {
+            if is_plugin_file(&path) {
+                plugin_files.push(path);
+            }
             continue;
         }
-        let path = path.unwrap();
 
-        if path.path().is_dir() {
-            continue;
+        let bundle = match fs::read_dir(&path) {
+            Ok(bundle) => bundle,
+            Err(error) => {
+                report.add_diagnostic(PluginDiagnostic::from_io(&path, &error));
+                continue;
+            },
+        };
+
+        for bundled in bundle {
+            let bundled = match bundled {
+                Ok(entry) => entry.path(),
+                Err(error) => {
+                    report.add_diagnostic(PluginDiagnostic::from_io(&path, &error));
+                    continue;
+                },
+            };
+
+            if bundled.is_file() && is_plugin_file(&bundled) {
+                plugin_files.push(bundled);
+            }
         }
+    }
+
+    plugin_files.sort();
 
-        let file = File::open(path.path())?;
-        let mut reader = BufReader::new(file);
-        let mut buffer = Vec::new();
+    plugin_files
+}
 
-        _ = reader.read_to_end(&mut buffer);
+/// Whether `path` is named like a plugin, so that other files kept with plugins, such as
+/// READMEs, aren't loaded as plugins.
+fn is_plugin_file(path: &Path) -> bool { path.extension().is_some_and(|ext| ext == "ron") }
 
-        let contents = String::from_utf8(buffer)?;
-        if let Ok(config) = ron::from_str::<LanguageConfig>(&contents) {
-            language_configurations.insert(config);
-        } else {
-            // ignore error cases, just continue on to the next step.
-            // TODO: log to an error file.
-            continue;
+/// Reads and parses the plugin file at `path`, from a plugin directory of `scope`,
+/// describing what is wrong with it if it can't be loaded.
+pub(super) fn load_plugin_file(
+    path: &Path,
+    scope: PluginScope,
+) -> std::result::Result<LanguageConfig, PluginDiagnostic> {
+    let mut buffer = Vec::new();
+
+    File::open(path)
+        .and_then(|file| BufReader::new(file).read_to_end(&mut buffer))
+        .map_err(|error| PluginDiagnostic::from_io(path, &error))?;
+
+    let contents = String::from_utf8(buffer)
+        .map_err(|_| PluginDiagnostic::new(path, "the plugin isn't valid UTF-8"))?;
+    let header = PluginHeader::read(&contents);
+
+    header.check_schema(path)?;
+    header.check_proman_version(path)?;
+
+    let mut config = parse_language_config(&contents)
+        .map_err(|error| PluginDiagnostic::from_ron(path, &error))?;
+
+    // plugins written for older schemas are migrated as they are loaded, so that they
+    // keep working until they are rewritten with `proman plugins migrate`.
+    schema::migrate(&mut config);
+
+    config.source = Some(PluginSource::File(path.to_owned(), scope));
+
+    Ok(config)
+}
+
+/// Where plugins are loaded from. Plugins are looked for in the directories given with
+/// `--plugin-dir`, then in those in [`PLUGIN_PATH_VAR`](super::PLUGIN_PATH_VAR), then in
+/// the `.proman/plugins` directory of the project `proman` is run in, then in the user's
+/// plugin directory, then in the system's plugin directories, and finally in the built-in
+/// plugins. A plugin overrides the plugins for the same language, ignoring case, that are
+/// found after it, which are then shadowed by it.
+#[derive(Clone, Debug)]
+pub struct PluginLoader {
+    /// The plugin directories, from the highest precedence to the lowest.
+    dirs:     Vec<(PluginScope, PathBuf)>,
+    builtins: bool,
+}
+
+impl PluginLoader {
+    /// A loader for the usual plugin directories and the built-in plugins. The user's
+    /// plugin directory is created if it doesn't exist yet. A directory that is found
+    /// more than once is only searched where it has the highest precedence.
+    pub fn new() -> Result<Self> {
+        let mut found = get_env_plugin_dirs()
+            .into_iter()
+            .map(|dir| (PluginScope::Environment, dir))
+            .collect::<Vec<_>>();
+
+        if let Some(dir) = find_project_plugin_dir(&env::current_dir()?) {
+            found.push((PluginScope::Project, dir));
+        }
+        found.push((PluginScope::User, get_language_plugin_dir()?));
+        found.extend(
+            get_system_plugin_dirs()
+                .into_iter()
+                .map(|dir| (PluginScope::System, dir)),
+        );
+
+        let mut dirs = Vec::<(PluginScope, PathBuf)>::new();
+        for (scope, dir) in found {
+            if !dirs.iter().any(|(_, other)| *other == dir) {
+                dirs.push((scope, dir));
+            }
         }
+
+        Ok(Self {
+            dirs,
+            builtins: true,
+        })
     }
 
+    /// Adds `dir` to the plugin directories, after the others of the same scope. A
+    /// directory that is already one of them is moved to `scope`, so that its plugins
+    /// aren't loaded twice.
+    pub fn with_dir(mut self, scope: PluginScope, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+
+        self.dirs.retain(|(_, other)| *other != dir);
+        self.dirs.push((scope, dir));
+        // the sort is stable, so directories of the same scope keep their order.
+        self.dirs.sort_by_key(|(scope, _)| *scope);
+        self
+    }
+
+    /// Sets whether the built-in plugins are loaded. Without them, only the plugins in
+    /// the plugin directories can be used.
+    pub fn with_builtins(mut self, builtins: bool) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    pub fn dirs(&self) -> &[(PluginScope, PathBuf)] { &self.dirs }
+    pub fn builtins(&self) -> bool { self.builtins }
+
+    /// Loads the plugins in order of precedence, reporting the plugins that were shadowed
+    /// and what is wrong with any plugin that couldn't be loaded rather than failing.
+    /// Plugins that extend another are merged into it, see [`resolve_extends`].
+    pub fn load(&self) -> Result<PluginReport> {
+        let mut report = PluginReport::default();
+        let mut plugins = Vec::new();
+
+        for (scope, dir) in &self.dirs {
+            // only the user's plugin directory is created, the others may not exist.
+            if !dir.is_dir() {
+                continue;
+            }
+
+            for path in find_plugin_files(dir, &mut report) {
+                match load_plugin_file(&path, *scope) {
+                    Ok(config) => plugins.push(config),
+                    Err(diagnostic) => report.add_diagnostic(diagnostic),
+                }
+            }
+        }
+
+        if self.builtins {
+            plugins.extend(parse_default_language_configs()?);
+        }
+
+        for resolved in resolve_extends(&plugins) {
+            match resolved {
+                Ok(config) => report.add_config(config),
+                Err(diagnostic) => report.add_diagnostic(diagnostic),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Rewrites the plugin files in the plugin directories that are written for an older
+    /// schema, see [`migrate_plugin_file`]. The built-in plugins are always up to date.
+    pub fn migrate(
+        &self,
+        dry_run: bool,
+    ) -> Result<Vec<(PathBuf, std::result::Result<Migration, PluginDiagnostic>)>> {
+        let mut report = PluginReport::default();
+        let mut migrated = Vec::new();
+
+        for (_, dir) in &self.dirs {
+            if !dir.is_dir() {
+                continue;
+            }
+
+            for path in find_plugin_files(dir, &mut report) {
+                let migration = migrate_plugin_file(&path, dry_run);
+
+                migrated.push((path, migration));
+            }
+        }
+
+        for diagnostic in report.diagnostics {
+            migrated.push((diagnostic.path().to_owned(), Err(diagnostic)));
+        }
+
+        Ok(migrated)
+    }
+}
+
+/// Merges each of `plugins` that extends another into the plugin it extends, keeping the
+/// order of the plugins, which is their order of precedence.
+fn resolve_extends(
+    plugins: &[LanguageConfig],
+) -> Vec<std::result::Result<LanguageConfig, PluginDiagnostic>> {
+    (0..plugins.len())
+        .map(|index| resolve_plugin(plugins, index))
+        .collect()
+}
+
+/// Finds the plugin that the plugin at `index` of `plugins` extends, if it extends one. A
+/// plugin that extends its own language extends the plugin it would otherwise override;
+/// otherwise, it extends the plugin for that language with the highest precedence.
+fn find_parent(plugins: &[LanguageConfig], index: usize) -> Option<usize> {
+    let config = &plugins[index];
+    let extends = config.extends.as_ref()?;
+    let extends_own = config.language.eq_ignore_ascii_case(extends);
+
+    plugins
+        .iter()
+        .enumerate()
+        .filter(|(other, _)| {
+            if extends_own {
+                *other > index
+            } else {
+                *other != index
+            }
+        })
+        .find(|(_, other)| other.language.eq_ignore_ascii_case(extends))
+        .map(|(other, _)| other)
+}
+
+/// Resolves the plugin at `index` of `plugins`, merging it into the plugin it extends
+/// once that has been resolved in turn.
+fn resolve_plugin(
+    plugins: &[LanguageConfig],
+    index: usize,
+) -> std::result::Result<LanguageConfig, PluginDiagnostic> {
+    let config = &plugins[index];
+    let Some(ref extends) = config.extends else {
+        return Ok(config.clone());
+    };
+
+    let path = config
+        .source
+        .as_ref()
+        .map_or_else(PathBuf::new, |source| source.path().to_owned());
+
+    // the plugins it extends are followed first, so that a plugin that ends up extending
+    // itself doesn't resolve forever.
+    let mut chain = vec![index];
+    while let Some(parent) = find_parent(plugins, *chain.last().unwrap()) {
+        let looped = chain.contains(&parent);
+        chain.push(parent);
+
+        if looped {
+            let languages = chain
+                .iter()
+                .map(|index| plugins[*index].language.as_str())
+                .collect::<Vec<_>>();
+
+            let problem = if parent == index {
+                "the plugin ends up extending itself"
+            } else {
+                "the plugins it extends go round in a loop"
+            };
+
+            return Err(PluginDiagnostic::new(
+                &path,
+                format!("{problem}: {}", languages.join(" -> ")),
+            ));
+        }
+    }
+
+    let Some(parent) = find_parent(plugins, index) else {
+        let languages = plugins
+            .iter()
+            .map(|plugin| plugin.language.as_str())
+            .collect::<Vec<_>>();
+
+        return Err(PluginDiagnostic::new(
+            &path,
+            format!("there is no plugin for \"{extends}\" to extend"),
+        )
+        .with_suggestion(
+            nearest(extends, &languages)
+                .map(|language| format!("did you mean `extends: \"{language}\"`?")),
+        ));
+    };
+
+    let parent = resolve_plugin(plugins, parent).map_err(|diagnostic| {
+        PluginDiagnostic::new(
+            &path,
+            format!(
+                "the plugin it extends, \"{extends}\", couldn't be loaded: {}",
+                diagnostic.message()
+            ),
+        )
+    })?;
+
+    config
+        .inherit(&parent)
+        .map_err(|reason| PluginDiagnostic::new(&path, reason))
+}
+
+/// Finds the `.proman/plugins` directory of the project that `dir` is in, looking in
+/// `dir` and then the directories containing it.
+fn find_project_plugin_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_PLUGIN_DIR))
+        .find(|dir| dir.is_dir())
+}
+
+/// The plugins that were loaded, along with the plugins they override and what was wrong
+/// with the plugins that couldn't be loaded.
+#[derive(Clone, Debug, Default)]
+pub struct PluginReport {
+    configs:     BTreeSet<LanguageConfig>,
+    /// Where the plugins that were overridden came from, by the language of the plugin
+    /// that overrides them.
+    shadowed:    BTreeMap<String, Vec<PluginSource>>,
+    diagnostics: Vec<PluginDiagnostic>,
+}
+
+impl PluginReport {
+    pub fn configs(&self) -> &BTreeSet<LanguageConfig> { &self.configs }
+    pub fn diagnostics(&self) -> &[PluginDiagnostic] { &self.diagnostics }
+    pub fn into_configs(self) -> BTreeSet<LanguageConfig> { self.configs }
+
+    /// Where the plugins that `config` overrides came from, from the highest precedence
+    /// to the lowest.
+    pub fn shadowed(&self, config: &LanguageConfig) -> &[PluginSource] {
+        self.shadowed
+            .get(&config.language)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds a plugin, unless a plugin for the same language has already been added, in
+    /// which case the new one is shadowed by it.
+    fn add_config(&mut self, config: LanguageConfig) {
+        let overriding = self
+            .configs
+            .iter()
+            .find(|loaded| loaded.language.eq_ignore_ascii_case(&config.language));
+
+        match overriding {
+            Some(overriding) =>
+                if let Some(source) = config.source {
+                    self.shadowed
+                        .entry(overriding.language.clone())
+                        .or_default()
+                        .push(source);
+                },
+            None => {
+                self.configs.insert(config);
+            },
+        }
+    }
+
+    fn add_diagnostic(&mut self, diagnostic: PluginDiagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Finds the plugin for `language`, ignoring case.
+    pub fn find(&self, language: &str) -> Result<LanguageConfig> {
+        self.configs
+            .iter()
+            .find(|config| config.language.eq_ignore_ascii_case(language))
+            .cloned()
+            .ok_or_else(|| Error::UnknownLanguage(language.to_owned()))
+    }
+
+    /// Finds the plugin for the project that `dir` is in, out of the plugins that can add
+    /// members to existing projects. If projects of several plugins contain `dir`, the
+    /// one with the nearest root is chosen.
+    pub fn detect_project(&self, dir: &Path) -> Result<LanguageConfig> {
+        let mut found = self
+            .configs
+            .iter()
+            .filter(|config| !config.add_member.is_empty())
+            .filter_map(|config| Some((config.find_project(dir)?, config)))
+            .collect::<Vec<_>>();
+
+        // the nearest root is the longest, as they all contain `dir`.
+        let Some(nearest) = found.iter().map(|(root, _)| root.clone()).max() else {
+            return Err(Error::NoProjectFound(dir.to_owned()));
+        };
+        found.retain(|(root, _)| *root == nearest);
+
+        match found.as_slice() {
+            [(_, config)] => Ok((*config).clone()),
+            _ => Err(Error::AmbiguousProject {
+                path:      nearest,
+                languages: found
+                    .iter()
+                    .map(|(_, config)| config.language.clone())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        }
+    }
+}
+
+/// Loads the plugins with a [`PluginLoader`] for the usual plugin directories and the
+/// built-in plugins.
+pub fn load_plugins() -> Result<PluginReport> { PluginLoader::new()?.load() }
+
+/// Parse the plugins in the plugins directory, specified at runtime and return the
+/// available language configurations that could be parsed. Plugins that couldn't be
+/// parsed are left out, see [`load_plugins`] for why.
+pub fn parse_language_configs() -> Result<BTreeSet<LanguageConfig>> {
+    let language_configurations = load_plugins()?.into_configs();
+
     if language_configurations.is_empty() {
         Err(Error::NoConfigurations)
     } else {
@@ -144,136 +913,335 @@ pub(crate) fn parse_language_configs() -> Result<BTreeSet<LanguageConfig>> {
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub(crate) enum RunningConfigMessage {
-    SetCommandStepText(String),
-    StartInputPrompt,
-    StartChoicePrompt,
-    PromptForProjectName(mpsc::Sender<String>),
-    PromptForProjectType {
-        available_types: BTreeSet<ProjectType>,
-        channel:         mpsc::Sender<ProjectType>,
-    },
-    CommandOutput(String),
-    #[default]
-    NoOp,
+/// Finds the language configuration for `language` in the parsed plugins, ignoring case.
+pub fn find_language_config(language: &str) -> Result<LanguageConfig> {
+    load_plugins()?.find(language)
 }
 
-#[derive(Copy, Clone, Debug, thiserror::Error)]
-pub enum RunnerError {
-    #[error("the runner has already been started, but there seems to be no bus to send from?")]
-    AlreadyStartedButNoBus,
-}
+/// Finds the plugin for the project that `dir` is in, see
+/// [`PluginReport::detect_project`].
+pub fn detect_project(dir: &Path) -> Result<LanguageConfig> { load_plugins()?.detect_project(dir) }
 
-type CommandBusType = Option<Arc<Mutex<Bus<(RunningConfigMessage, bool)>>>>;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(Clone, Debug)]
-pub(crate) struct LanguageConfigRunner {
-    commands:      Vec<CommandStep>,
-    project_types: BTreeSet<ProjectType>,
-    project_name:  Arc<RwLock<String>>,
-    project_type:  Arc<RwLock<ProjectType>>,
-    has_started:   bool,
-    command_bus:   CommandBusType,
-}
+    fn step(id: &str) -> CommandStep {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&format!(
+                r#"Step(name: "{id}", id: "{id}", command: CopyTemplate)"#
+            ))
+            .unwrap()
+    }
+
+    fn steps(ids: &[&str]) -> Vec<CommandStep> { ids.iter().map(|id| step(id)).collect() }
+
+    fn ids(steps: &[CommandStep]) -> Vec<&str> {
+        steps.iter().map(|step| step.id().unwrap()).collect()
+    }
+
+    fn plugin(source: &str, path: &str) -> LanguageConfig {
+        let mut config = parse_language_config(source).unwrap();
+        config.source = Some(PluginSource::File(PathBuf::from(path), PluginScope::User));
+        config
+    }
+
+    /// An empty directory of its own in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("proman-parser-{}-{name}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn changed(change: StepChange) -> Vec<CommandStep> {
+        let mut changed = steps(&["a", "b", "c"]);
+        change.apply(&mut changed).unwrap();
+        changed
+    }
+
+    #[test]
+    fn replaces_a_step() {
+        assert_eq!(
+            ids(&changed(StepChange::Replace(
+                String::from("b"),
+                Box::new(step("x"))
+            ))),
+            ["a", "x", "c"]
+        );
+    }
+
+    #[test]
+    fn removes_a_step() {
+        assert_eq!(
+            ids(&changed(StepChange::Remove(String::from("b")))),
+            ["a", "c"]
+        );
+    }
+
+    #[test]
+    fn inserts_steps_around_a_step() {
+        assert_eq!(
+            ids(&changed(StepChange::InsertBefore(
+                String::from("a"),
+                steps(&["x", "y"])
+            ))),
+            ["x", "y", "a", "b", "c"]
+        );
+        assert_eq!(
+            ids(&changed(StepChange::InsertAfter(
+                String::from("b"),
+                steps(&["x"])
+            ))),
+            ["a", "b", "x", "c"]
+        );
+        assert_eq!(
+            ids(&changed(StepChange::InsertAfter(
+                String::from("c"),
+                steps(&["x"])
+            ))),
+            ["a", "b", "c", "x"]
+        );
+    }
+
+    #[test]
+    fn adds_steps_at_either_end() {
+        assert_eq!(
+            ids(&changed(StepChange::Prepend(steps(&["x"])))),
+            ["x", "a", "b", "c"]
+        );
+        assert_eq!(
+            ids(&changed(StepChange::Append(steps(&["x"])))),
+            ["a", "b", "c", "x"]
+        );
+    }
+
+    #[test]
+    fn fails_to_change_a_missing_step() {
+        let missing = String::from("missing");
 
-impl LanguageConfigRunner {
-    fn new(
-        commands: Vec<CommandStep>,
-        project_types: BTreeSet<ProjectType>,
-    ) -> LanguageConfigRunner {
-        LanguageConfigRunner {
-            commands,
-            project_types,
-            project_name: Arc::new(RwLock::new(String::new())),
-            project_type: Arc::new(RwLock::new(ProjectType::Binary)),
-            has_started: false,
-            command_bus: None,
+        for change in [
+            StepChange::InsertBefore(missing.clone(), steps(&["x"])),
+            StepChange::InsertAfter(missing.clone(), steps(&["x"])),
+            StepChange::Replace(missing.clone(), Box::new(step("x"))),
+            StepChange::Remove(missing.clone()),
+        ] {
+            let mut steps = steps(&["a", "b", "c"]);
+            let error = change.apply(&mut steps).unwrap_err();
+
+            assert!(error.contains("\"missing\""), "{error}");
+            assert_eq!(ids(&steps), ["a", "b", "c"]);
         }
     }
 
-    /// Start or continue the current runner. If the current runner is already running and
-    /// has a set [`Self::command_bus`] for the recipient, then the function returns
-    /// early, a cloned version of the reference-counted [`Self::command_bus`].
-    pub fn start_or_continue(
-        &mut self,
-    ) -> std::result::Result<BusReader<(RunningConfigMessage, bool)>, RunnerError> {
-        if let Some(ref mut bus) = self.command_bus
-            && self.has_started
-        {
-            return Ok(bus.lock().unwrap().add_rx());
-        } else if self.has_started {
-            return Err(RunnerError::AlreadyStartedButNoBus);
+    #[test]
+    fn resolves_a_chain_of_extends() {
+        let plugins = [
+            plugin(
+                r#"(
+                    language: "C",
+                    extends: "B",
+                    step_changes: [Remove("a"), InsertBefore("b_own", [Step(name: "x", id: "x", command: CopyTemplate)])],
+                    command_steps: [Step(name: "c_own", id: "c_own", command: CopyTemplate)],
+                )"#,
+                "c.ron",
+            ),
+            plugin(
+                r#"(
+                    language: "B",
+                    extends: "A",
+                    step_changes: [Replace("b", Step(name: "b2", id: "b2", command: CopyTemplate))],
+                    command_steps: [Step(name: "b_own", id: "b_own", command: CopyTemplate)],
+                )"#,
+                "b/b.ron",
+            ),
+            plugin(
+                r#"(
+                    language: "A",
+                    command_steps: [
+                        Step(name: "a", id: "a", command: CopyTemplate),
+                        Step(name: "b", id: "b", command: CopyTemplate),
+                        Step(name: "keep", id: "keep", command: CopyTemplate),
+                    ],
+                )"#,
+                "a/a.ron",
+            ),
+        ];
+
+        let resolved = resolve_extends(&plugins)
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        let c = &resolved[0];
+
+        assert_eq!(
+            ids(c.command_steps()),
+            ["b2", "keep", "x", "b_own", "c_own"]
+        );
+        assert_eq!(c.source().unwrap().path(), Path::new("c.ron"));
+
+        // each step reads its files from the plugin that it was given in.
+        let sources = c
+            .command_steps()
+            .iter()
+            .map(|step| step.source().map(PluginSource::path))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            [
+                Some(Path::new("b/b.ron")),
+                Some(Path::new("a/a.ron")),
+                None,
+                Some(Path::new("b/b.ron")),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_plugins_that_extend_their_own_language() {
+        let plugins = [
+            plugin(
+                r#"(language: "Rust", extends: "Rust", command_steps: [Step(name: "project", id: "project", command: CopyTemplate)])"#,
+                "project.ron",
+            ),
+            plugin(
+                r#"(language: "Rust", extends: "Rust", command_steps: [Step(name: "user", id: "user", command: CopyTemplate)])"#,
+                "user.ron",
+            ),
+            plugin(
+                r#"(language: "Rust", command_steps: [Step(name: "builtin", id: "builtin", command: CopyTemplate)])"#,
+                "builtin.ron",
+            ),
+        ];
+
+        let resolved = resolve_plugin(&plugins, 0).unwrap();
+
+        assert_eq!(
+            ids(resolved.command_steps()),
+            ["builtin", "user", "project"]
+        );
+    }
+
+    #[test]
+    fn orders_plugin_directories_by_scope() {
+        let loader = PluginLoader {
+            dirs:     Vec::new(),
+            builtins: true,
         }
+        .with_dir(PluginScope::User, "user")
+        .with_dir(PluginScope::Flag, "first")
+        .with_dir(PluginScope::Flag, "second")
+        .with_dir(PluginScope::Project, "user");
 
-        self.has_started = true;
-
-        let command_tx = Arc::new(Mutex::new(Bus::new(4096)));
-        let command_rx = command_tx.lock().unwrap().add_rx();
-        self.command_bus = Some(command_tx.clone());
-
-        let Self {
-            commands,
-            project_types: available_types,
-            project_name: name_lock,
-            project_type: type_lock,
-            ..
-        } = self.clone();
-
-        std::thread::spawn(move || {
-            commands.iter().for_each(|step| {
-                command_tx.lock().unwrap().broadcast((
-                    RunningConfigMessage::SetCommandStepText(step.name.clone()),
-                    false,
-                ));
-
-                match &step.command {
-                    CommandType::PromptProjectName => {
-                        command_tx
-                            .lock()
-                            .unwrap()
-                            .broadcast((RunningConfigMessage::StartInputPrompt, false));
-
-                        let (name_tx, name_rx) = mpsc::channel();
-                        command_tx.lock().unwrap().broadcast((
-                            RunningConfigMessage::PromptForProjectName(name_tx),
-                            false,
-                        ));
-
-                        if let Ok(name) = name_rx.recv() {
-                            *name_lock.write().unwrap() = name;
-                        }
-                    },
-                    CommandType::PromptProjectType => {
-                        command_tx
-                            .lock()
-                            .unwrap()
-                            .broadcast((RunningConfigMessage::StartChoicePrompt, false));
-
-                        let (type_tx, type_rx) = mpsc::channel();
-                        command_tx.lock().unwrap().broadcast((
-                            RunningConfigMessage::PromptForProjectType {
-                                available_types: available_types.clone(),
-                                channel:         type_tx,
-                            },
-                            false,
-                        ));
-
-                        if let Ok(project_type) = type_rx.recv() {
-                            *type_lock.write().unwrap() = project_type;
-                        }
-                    },
-                    CommandType::Command(command, arguments) => (),
-                    _ => {},
-                }
-            });
+        assert_eq!(
+            loader.dirs(),
+            [
+                (PluginScope::Flag, PathBuf::from("first")),
+                (PluginScope::Flag, PathBuf::from("second")),
+                (PluginScope::Project, PathBuf::from("user")),
+            ]
+        );
+    }
+
+    #[test]
+    fn plugins_override_those_for_the_same_language_ignoring_case() {
+        let dir = temp_dir("precedence");
+        let flag = dir.join("flag");
+        let user = dir.join("user");
+        fs::create_dir(&flag).unwrap();
+        fs::create_dir(&user).unwrap();
+        fs::write(
+            flag.join("rust.ron"),
+            r#"(language: "RUST", command_steps: [])"#,
+        )
+        .unwrap();
+        fs::write(
+            user.join("rust.ron"),
+            r#"(language: "Rust", command_steps: [])"#,
+        )
+        .unwrap();
+        fs::write(
+            user.join("python.ron"),
+            r#"(language: "Python", command_steps: [])"#,
+        )
+        .unwrap();
+
+        let report = PluginLoader {
+            dirs:     Vec::new(),
+            builtins: true,
+        }
+        .with_dir(PluginScope::User, &user)
+        .with_dir(PluginScope::Flag, &flag)
+        .load()
+        .unwrap();
+
+        let rust = report.find("rust").unwrap();
+        assert_eq!(rust.language(), "RUST");
+        assert_eq!(
+            rust.source(),
+            Some(&PluginSource::File(
+                flag.join("rust.ron"),
+                PluginScope::Flag
+            ))
+        );
+        let shadowed = report.shadowed(&rust);
+        assert_eq!(shadowed.len(), 2);
+        assert_eq!(
+            shadowed[0],
+            PluginSource::File(user.join("rust.ron"), PluginScope::User)
+        );
+        assert_eq!(shadowed[1].scope(), PluginScope::BuiltIn);
+
+        let python = report.find("PYTHON").unwrap();
+        assert!(report.shadowed(&python).is_empty());
+        assert!(report.find("golang").is_ok());
+        assert_eq!(
+            report
+                .configs()
+                .iter()
+                .filter(|config| config.language().eq_ignore_ascii_case("rust"))
+                .count(),
+            1
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_out_the_built_in_plugins() {
+        let report = PluginLoader {
+            dirs:     Vec::new(),
+            builtins: false,
+        }
+        .load()
+        .unwrap();
+
+        assert!(report.configs().is_empty());
+        assert!(matches!(
+            report.find("rust"),
+            Err(Error::UnknownLanguage(language)) if language == "rust"
+        ));
+    }
+
+    #[test]
+    fn reports_plugin_directories_that_cant_be_read() {
+        let dir = temp_dir("unreadable");
+        fs::write(dir.join("python.ron"), "").unwrap();
+        fs::write(dir.join("README.md"), "").unwrap();
+        let mut report = PluginReport::default();
+
+        let missing = dir.join("missing");
+        assert!(find_plugin_files(&missing, &mut report).is_empty());
+        assert_eq!(report.diagnostics().len(), 1);
+        assert_eq!(report.diagnostics()[0].path(), missing);
 
-            command_tx
-                .lock()
-                .unwrap()
-                .broadcast((RunningConfigMessage::NoOp, true));
-        });
+        assert_eq!(
+            find_plugin_files(&dir, &mut report),
+            [dir.join("python.ron")]
+        );
 
-        Ok(command_rx)
+        fs::remove_dir_all(dir).unwrap();
     }
 }
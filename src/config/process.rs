@@ -0,0 +1,368 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    ffi::OsString,
+    fmt,
+    io::{self, BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::RunnerError;
+
+/// How often a running command is checked for having been cancelled or timed out while
+/// waiting for it to write some output.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long the output of a command that has exited is still read for. Processes it
+/// started in the background can keep its output open, which would otherwise leave the
+/// step waiting for them to finish too.
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+/// How many lines of output can be waiting to be handled before the threads reading the
+/// output of a command wait for them to be, so that a command that writes faster than
+/// its output is shown doesn't use more and more memory.
+const OUTPUT_CAPACITY: usize = 1024;
+
+/// The process ids of every command that is currently running, so that they can be killed
+/// if `proman` itself is told to stop.
+static RUNNING_COMMANDS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// The resolved working directory and environment that a command is run with.
+#[derive(Clone, Debug, Default)]
+pub struct CommandEnvironment {
+    pub(super) working_dir: PathBuf,
+    pub(super) env:         BTreeMap<String, String>,
+    pub(super) path:        Vec<PathBuf>,
+}
+
+impl CommandEnvironment {
+    /// The directories that commands are looked up in, which are the directories of the
+    /// plugin's `path` followed by those of `PATH`.
+    pub(super) fn search_path(&self) -> Vec<PathBuf> {
+        let existing = self
+            .env
+            .get("PATH")
+            .map(OsString::from)
+            .or_else(|| env::var_os("PATH"))
+            .unwrap_or_default();
+
+        self.path
+            .iter()
+            .cloned()
+            .chain(env::split_paths(&existing))
+            .collect()
+    }
+}
+
+impl fmt::Display for CommandEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in {}", self.working_dir.display())?;
+
+        let mut overrides = self
+            .env
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+
+        if !self.path.is_empty() {
+            let path = self
+                .path
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>();
+
+            overrides.push(format!("PATH += {}", path.join(", ")));
+        }
+
+        if !overrides.is_empty() {
+            write!(f, " ({})", overrides.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Kills every command that is currently running, along with any processes they started.
+pub fn kill_running_commands() {
+    let running = std::mem::take(&mut *RUNNING_COMMANDS.lock().unwrap());
+
+    for pid in running {
+        kill_tree(pid);
+    }
+}
+
+/// Makes the process its own process group leader, so that it and every process it starts
+/// can be killed together with [`kill_tree`].
+#[cfg(unix)]
+fn isolate(process: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    process.process_group(0);
+}
+
+#[cfg(windows)]
+fn isolate(process: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+    process.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Kills the process with the given id, along with every process it started.
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+
+    // SAFETY: `kill` has no memory safety requirements, and the negated pid addresses the
+    // process group that `isolate` created for the command.
+    unsafe {
+        libc::kill(-pid, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_tree(pid: u32) {
+    _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Reads `reader` line by line on a separate thread, sending each line down `tx` until
+/// either the reader is exhausted or the receiving end hangs up. Sending waits while the
+/// channel is full, which leaves the command waiting to write until there is room.
+///
+/// The thread is never waited for: processes that a command starts in the background,
+/// including ones that left its process group, can keep the output open long after the
+/// command itself is gone, and the thread only finishes once they close it or write to
+/// it again.
+fn forward_lines<R>(reader: R, tx: mpsc::SyncSender<String>)
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        for line in BufReader::new(reader)
+            .lines()
+            .map_while(std::result::Result::ok)
+        {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Hands the output that is left in `output_rx` to `on_output`, until either every
+/// process writing to it has closed it or [`OUTPUT_GRACE`] has passed.
+fn drain_output<F>(output_rx: &mpsc::Receiver<String>, on_output: &mut F)
+where
+    F: FnMut(String),
+{
+    let deadline = Instant::now() + OUTPUT_GRACE;
+
+    while let Ok(line) = output_rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+    {
+        on_output(line);
+    }
+}
+
+/// Kills `child` and everything it started, then waits for it so that it doesn't linger.
+fn stop(child: &mut Child) {
+    kill_tree(child.id());
+    _ = child.kill();
+    _ = child.wait();
+}
+
+/// Runs `command` with the given `arguments`, calling `on_output` for every line written
+/// to either stdout or stderr as it arrives. Returns the exit status of the process once
+/// it has finished. If the command takes longer than `timeout`, or `cancelled` is set
+/// while it is running, the command and every process it started are killed.
+pub(super) fn run_command<F>(
+    command: &str,
+    arguments: &[String],
+    environment: &CommandEnvironment,
+    timeout: Option<Duration>,
+    cancelled: &AtomicBool,
+    mut on_output: F,
+) -> std::result::Result<ExitStatus, RunnerError>
+where
+    F: FnMut(String),
+{
+    let could_not_spawn = |error: io::Error| RunnerError::CouldNotSpawnCommand {
+        command: command.to_owned(),
+        reason:  error.to_string(),
+    };
+
+    let mut process = Command::new(command);
+    process
+        .args(arguments)
+        .current_dir(&environment.working_dir)
+        .envs(&environment.env);
+
+    if !environment.path.is_empty() {
+        let path = env::join_paths(environment.search_path())
+            .map_err(|error| could_not_spawn(io::Error::other(error)))?;
+
+        process.env("PATH", path);
+    }
+
+    isolate(&mut process);
+
+    let mut child = process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(could_not_spawn)?;
+
+    RUNNING_COMMANDS.lock().unwrap().insert(child.id());
+
+    let (output_tx, output_rx) = mpsc::sync_channel(OUTPUT_CAPACITY);
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, output_tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, output_tx.clone());
+    }
+    drop(output_tx);
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let result = loop {
+        match output_rx.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => on_output(line),
+            Err(RecvTimeoutError::Disconnected) => break child.wait().map_err(could_not_spawn),
+            Err(RecvTimeoutError::Timeout) => (),
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                drain_output(&output_rx, &mut on_output);
+                break Ok(status);
+            },
+            Ok(None) => (),
+            Err(error) => {
+                stop(&mut child);
+                break Err(could_not_spawn(error));
+            },
+        }
+
+        if cancelled.load(Ordering::SeqCst) {
+            stop(&mut child);
+            break Err(RunnerError::Cancelled);
+        }
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            stop(&mut child);
+            break Err(RunnerError::TimedOut {
+                command: command.to_owned(),
+                seconds: timeout.unwrap_or_default().as_secs(),
+            });
+        }
+    };
+
+    RUNNING_COMMANDS.lock().unwrap().remove(&child.id());
+
+    result
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(
+        script: &str,
+        timeout: Option<Duration>,
+    ) -> (Result<ExitStatus, RunnerError>, Vec<String>) {
+        let mut output = Vec::new();
+        let environment = CommandEnvironment {
+            working_dir: env::temp_dir(),
+            ..CommandEnvironment::default()
+        };
+        let result = run_command(
+            "sh",
+            &[String::from("-c"), String::from(script)],
+            &environment,
+            timeout,
+            &AtomicBool::new(false),
+            |line| output.push(line),
+        );
+
+        (result, output)
+    }
+
+    #[test]
+    fn forwards_output() {
+        let (result, output) = sh("echo out; echo err >&2; exit 3", None);
+
+        assert_eq!(result.unwrap().code(), Some(3));
+        assert_eq!(output.len(), 2);
+        assert!(output.contains(&String::from("out")));
+        assert!(output.contains(&String::from("err")));
+    }
+
+    #[test]
+    fn finishes_when_the_command_exits() {
+        let started = Instant::now();
+        let (result, output) = sh("setsid sleep 4 & sleep 2 & echo started", None);
+
+        assert!(result.unwrap().success());
+        assert_eq!(output, ["started"]);
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "{:?}",
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn times_out_without_waiting_for_escaped_processes() {
+        let started = Instant::now();
+        let (result, _) = sh("setsid sleep 6 & sleep 30", Some(Duration::from_secs(1)));
+
+        assert!(matches!(
+            result,
+            Err(RunnerError::TimedOut { seconds: 1, .. })
+        ));
+        assert!(
+            started.elapsed() < Duration::from_secs(3),
+            "{:?}",
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let cancelled = AtomicBool::new(true);
+        let started = Instant::now();
+        let result = run_command(
+            "sleep",
+            &[String::from("30")],
+            &CommandEnvironment {
+                working_dir: env::temp_dir(),
+                ..CommandEnvironment::default()
+            },
+            None,
+            &cancelled,
+            |_| (),
+        );
+
+        assert!(matches!(result, Err(RunnerError::Cancelled)));
+        assert!(
+            started.elapsed() < Duration::from_secs(3),
+            "{:?}",
+            started.elapsed()
+        );
+    }
+}
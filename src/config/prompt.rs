@@ -0,0 +1,291 @@
+use std::collections::BTreeSet;
+
+use minijinja::Value;
+
+use super::{Frontend, GivenAnswer, Validation};
+
+/// A question asked by one of the generic prompt steps, such as
+/// [`super::CommandType::PromptChoice`], with its placeholders and defaults resolved.
+#[derive(Clone, Debug)]
+pub enum Question {
+    /// Asks for a line of text. An empty answer is replaced with the default, and the
+    /// result has to pass `validation`.
+    Text {
+        label:      String,
+        default:    Option<String>,
+        validation: Validation,
+    },
+    /// Asks for one of `options`, with the option at `default` selected to begin with.
+    Choice {
+        label:   String,
+        options: Vec<String>,
+        default: usize,
+    },
+    /// Asks a yes or no question.
+    Confirm { label: String, default: bool },
+    /// Asks for any number of `options`, with those at `default` selected to begin with.
+    MultiSelect {
+        label:   String,
+        options: Vec<String>,
+        default: BTreeSet<usize>,
+    },
+}
+
+/// The answer given to a [`Question`]. Options are referred to by their position in the
+/// question.
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Text(String),
+    Choice(usize),
+    Confirm(bool),
+    MultiSelect(BTreeSet<usize>),
+}
+
+impl Question {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Text { label, .. }
+            | Self::Choice { label, .. }
+            | Self::Confirm { label, .. }
+            | Self::MultiSelect { label, .. } => label,
+        }
+    }
+
+    /// The answer that is used when the user accepts the default.
+    pub fn default_answer(&self) -> Answer {
+        match self {
+            Self::Text { default, .. } => Answer::Text(default.clone().unwrap_or_default()),
+            Self::Choice { default, .. } => Answer::Choice(*default),
+            Self::Confirm { default, .. } => Answer::Confirm(*default),
+            Self::MultiSelect { default, .. } => Answer::MultiSelect(default.clone()),
+        }
+    }
+
+    /// Asks the question through `frontend`, returning [`None`] if it wasn't answered.
+    pub fn ask(&self, frontend: &mut dyn Frontend) -> Option<Answer> {
+        match self {
+            Self::Text {
+                label,
+                default,
+                validation,
+            } => frontend
+                .ask_text(label, default.as_deref(), validation)
+                .map(Answer::Text),
+            Self::Choice {
+                label,
+                options,
+                default,
+            } => frontend
+                .ask_choice(label, options, *default)
+                .map(Answer::Choice),
+            Self::Confirm { label, default } =>
+                frontend.ask_confirm(label, *default).map(Answer::Confirm),
+            Self::MultiSelect {
+                label,
+                options,
+                default,
+            } => frontend
+                .ask_multi_select(label, options, default)
+                .map(Answer::MultiSelect),
+        }
+    }
+
+    /// Parses an answer that was given before the run. A choice is given as one of the
+    /// options, ignoring case if no option matches exactly. A confirmation is given as a
+    /// boolean, or as `yes`, `no`, `true` or `false`, and a multi-select as a list of the
+    /// options that were selected, or as text with them separated by commas.
+    pub fn parse(&self, given: &GivenAnswer) -> Result<Answer, String> {
+        let option = |options: &[String], text: &str| {
+            options
+                .iter()
+                .position(|option| option == text)
+                .or_else(|| {
+                    options
+                        .iter()
+                        .position(|option| option.eq_ignore_ascii_case(text))
+                })
+                .ok_or_else(|| format!("the options are {}", options.join(", ")))
+        };
+
+        match (self, given) {
+            (Self::Text { .. }, GivenAnswer::Text(text)) => Ok(Answer::Text(text.clone())),
+            (Self::Choice { options, .. }, GivenAnswer::Text(text)) =>
+                option(options, text.trim()).map(Answer::Choice),
+            (Self::Confirm { .. }, GivenAnswer::Bool(confirmed)) => Ok(Answer::Confirm(*confirmed)),
+            (Self::Confirm { .. }, GivenAnswer::Text(text)) =>
+                match text.trim().to_lowercase().as_str() {
+                    "y" | "yes" | "true" => Ok(Answer::Confirm(true)),
+                    "n" | "no" | "false" => Ok(Answer::Confirm(false)),
+                    _ => Err(String::from("the answer has to be yes or no")),
+                },
+            (Self::Confirm { .. }, GivenAnswer::List(_)) =>
+                Err(String::from("the answer has to be yes or no")),
+            (Self::MultiSelect { options, .. }, GivenAnswer::List(selected)) => selected
+                .iter()
+                .map(|selected| option(options, selected.trim()))
+                .collect::<Result<_, _>>()
+                .map(Answer::MultiSelect),
+            (Self::MultiSelect { options, .. }, GivenAnswer::Text(text)) => text
+                .split(',')
+                .map(str::trim)
+                .filter(|selected| !selected.is_empty())
+                .map(|selected| option(options, selected))
+                .collect::<Result<_, _>>()
+                .map(Answer::MultiSelect),
+            (Self::MultiSelect { .. }, GivenAnswer::Bool(_)) =>
+                Err(String::from("the answer has to be a list of options")),
+            (Self::Text { .. } | Self::Choice { .. }, _) =>
+                Err(String::from("the answer has to be text")),
+        }
+    }
+
+    /// Checks that `answer` can be given to the question, returning what is wrong with it
+    /// if not. Only text answers can be refused; other answers are limited to the options
+    /// of the question already.
+    pub fn check(&self, answer: &Answer) -> Result<(), String> {
+        match (self, answer) {
+            (Self::Text { validation, .. }, Answer::Text(_)) =>
+                validation.check(&self.value_of(answer.clone()).to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Turns `answer` into the value of the question's variable. Text answers are
+    /// strings, chosen options are the text of the option, confirmations are booleans
+    /// and multiple selections are lists of the text of the options in the order they
+    /// were given in. An answer that doesn't fit the question is replaced with the
+    /// default.
+    pub fn value_of(&self, answer: Answer) -> Value {
+        match (self, answer) {
+            (Self::Text { default, .. }, Answer::Text(text)) => match default {
+                Some(default) if text.is_empty() => Value::from(default.clone()),
+                _ => Value::from(text),
+            },
+            (Self::Choice { options, .. }, Answer::Choice(index)) if index < options.len() =>
+                Value::from(options[index].clone()),
+            (Self::Confirm { .. }, Answer::Confirm(confirmed)) => Value::from(confirmed),
+            (Self::MultiSelect { options, .. }, Answer::MultiSelect(selected)) => Value::from(
+                options
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| selected.contains(index))
+                    .map(|(_, option)| option.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            _ => self.value_of(self.default_answer()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(options: &[&str]) -> Vec<String> {
+        options.iter().map(|option| option.to_string()).collect()
+    }
+
+    fn text(text: &str) -> GivenAnswer { GivenAnswer::Text(text.to_owned()) }
+
+    fn choice() -> Question {
+        Question::Choice {
+            label:   String::from("Runtime"),
+            options: options(&["tokio", "Tokio", "smol"]),
+            default: 0,
+        }
+    }
+
+    fn multi_select() -> Question {
+        Question::MultiSelect {
+            label:   String::from("Features"),
+            options: options(&["serde", "tokio", "tracing"]),
+            default: BTreeSet::new(),
+        }
+    }
+
+    fn confirm() -> Question {
+        Question::Confirm {
+            label:   String::from("Docker?"),
+            default: false,
+        }
+    }
+
+    #[test]
+    fn parses_text() {
+        let question = Question::Text {
+            label:      String::from("Name"),
+            default:    None,
+            validation: Validation::default(),
+        };
+
+        assert!(
+            matches!(question.parse(&text(" demo ")), Ok(Answer::Text(text)) if text == " demo ")
+        );
+        assert!(question.parse(&GivenAnswer::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn parses_choices_preferring_the_exact_case() {
+        assert!(matches!(
+            choice().parse(&text("Tokio")),
+            Ok(Answer::Choice(1))
+        ));
+        assert!(matches!(
+            choice().parse(&text(" tokio ")),
+            Ok(Answer::Choice(0))
+        ));
+        assert!(matches!(
+            choice().parse(&text("SMOL")),
+            Ok(Answer::Choice(2))
+        ));
+        assert_eq!(
+            choice().parse(&text("async-std")).unwrap_err(),
+            "the options are tokio, Tokio, smol"
+        );
+        assert!(choice()
+            .parse(&GivenAnswer::List(options(&["smol"])))
+            .is_err());
+    }
+
+    #[test]
+    fn parses_confirmations() {
+        for yes in ["y", "Yes", " true "] {
+            assert!(
+                matches!(confirm().parse(&text(yes)), Ok(Answer::Confirm(true))),
+                "{yes}"
+            );
+        }
+        for no in ["n", "NO", "false"] {
+            assert!(
+                matches!(confirm().parse(&text(no)), Ok(Answer::Confirm(false))),
+                "{no}"
+            );
+        }
+        assert!(matches!(
+            confirm().parse(&GivenAnswer::Bool(true)),
+            Ok(Answer::Confirm(true))
+        ));
+        assert!(confirm().parse(&text("maybe")).is_err());
+        assert!(confirm().parse(&GivenAnswer::List(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn parses_multi_selects_from_lists_or_text() {
+        let selected = |indexes: &[usize]| indexes.iter().copied().collect::<BTreeSet<_>>();
+
+        assert!(matches!(
+            multi_select().parse(&text("tracing, Serde,")),
+            Ok(Answer::MultiSelect(chosen)) if chosen == selected(&[0, 2])
+        ));
+        assert!(matches!(
+            multi_select().parse(&GivenAnswer::List(options(&["tokio"]))),
+            Ok(Answer::MultiSelect(chosen)) if chosen == selected(&[1])
+        ));
+        assert!(matches!(
+            multi_select().parse(&text("")),
+            Ok(Answer::MultiSelect(chosen)) if chosen.is_empty()
+        ));
+        assert!(multi_select().parse(&text("serde, log")).is_err());
+        assert!(multi_select().parse(&GivenAnswer::Bool(true)).is_err());
+    }
+}
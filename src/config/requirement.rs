@@ -0,0 +1,450 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::AtomicBool,
+    time::Duration,
+};
+
+use super::{
+    bundle::is_executable, process::run_command, runner::split_arguments, CommandEnvironment,
+};
+
+/// How long the version command of a tool can run for before it's given up on.
+const VERSION_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A version number made up of numeric components, such as `1.21.5`. Missing components
+/// are treated as zero when comparing, so `1.21` and `1.21.0` are equal.
+#[derive(Clone, Debug)]
+pub struct Version(Vec<u64>);
+
+impl Version {
+    /// Finds the first version number in the output of a version command, such as the
+    /// `1.21.5` in `go version go1.21.5 linux/amd64`. Numbers with more than one
+    /// component are preferred, so that a leading `2` in `v2 of tool 1.4` doesn't get
+    /// picked.
+    fn find_in(output: &str) -> Option<Self> {
+        let candidates = output
+            .split(|character: char| !character.is_ascii_digit() && character != '.')
+            .map(|candidate| candidate.trim_matches('.'))
+            .filter_map(|candidate| candidate.parse::<Self>().ok())
+            .collect::<Vec<_>>();
+
+        candidates
+            .iter()
+            .find(|version| version.0.len() > 1)
+            .or_else(|| candidates.first())
+            .cloned()
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        version
+            .split('.')
+            .map(|component| component.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+            .map_err(|_| format!("\"{version}\" is not a version number, such as 1.21"))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let components = self.0.iter().map(u64::to_string).collect::<Vec<_>>();
+
+        f.write_str(&components.join("."))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let length = self.0.len().max(other.0.len());
+        let component = |version: &Self, index| version.0.get(index).copied().unwrap_or(0);
+
+        (0..length)
+            .map(|index| component(self, index).cmp(&component(other, index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other).is_eq() }
+}
+
+impl Eq for Version {}
+
+/// How the version of a tool is compared against the version in a [`Requirement`].
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    const ALL: [Self; 5] = [
+        Self::LessOrEqual,
+        Self::GreaterOrEqual,
+        Self::Less,
+        Self::Greater,
+        Self::Equal,
+    ];
+
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Less => ordering.is_lt(),
+            Self::LessOrEqual => ordering.is_le(),
+            Self::Equal => ordering.is_eq(),
+            Self::GreaterOrEqual => ordering.is_ge(),
+            Self::Greater => ordering.is_gt(),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "=",
+            Self::GreaterOrEqual => ">=",
+            Self::Greater => ">",
+        })
+    }
+}
+
+/// A tool that has to be on `PATH` before a plugin is run, given in a plugin as an entry
+/// of its `requirements`. An entry is either just the name of the tool, such as
+/// `"cargo"`, or the name followed by a version constraint, such as `"go >= 1.21"`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Requirement {
+    tool:       String,
+    constraint: Option<(Comparison, Version)>,
+}
+
+impl Requirement {
+    pub fn tool(&self) -> &str { &self.tool }
+    pub fn constraint(&self) -> Option<&(Comparison, Version)> { self.constraint.as_ref() }
+}
+
+impl TryFrom<String> for Requirement {
+    type Error = String;
+
+    fn try_from(requirement: String) -> Result<Self, Self::Error> {
+        let Some(start) = requirement.find(['<', '>', '=']) else {
+            let tool = requirement.trim();
+
+            // a tool name can't have whitespace in it, so anything after it has to be a
+            // constraint with a comparison that isn't known.
+            return match tool {
+                "" => Err(format!("\"{requirement}\" doesn't name a tool")),
+                _ if tool.contains(char::is_whitespace) =>
+                    Err(format!("\"{requirement}\" has an unknown comparison")),
+                _ => Ok(Self {
+                    tool:       tool.to_owned(),
+                    constraint: None,
+                }),
+            };
+        };
+
+        let (tool, constraint) = requirement.split_at(start);
+        let (comparison, version) = Comparison::ALL
+            .into_iter()
+            .find_map(|comparison| {
+                let version = constraint
+                    .strip_prefix("==")
+                    .filter(|_| comparison == Comparison::Equal)
+                    .or_else(|| constraint.strip_prefix(&comparison.to_string()))?;
+
+                Some((comparison, version))
+            })
+            .ok_or_else(|| format!("\"{requirement}\" has an unknown comparison"))?;
+
+        if tool.trim().is_empty() {
+            return Err(format!("\"{requirement}\" doesn't name a tool"));
+        }
+
+        Ok(Self {
+            tool:       tool.trim().to_owned(),
+            constraint: Some((comparison, version.trim().parse()?)),
+        })
+    }
+}
+
+impl From<Requirement> for String {
+    fn from(requirement: Requirement) -> Self { requirement.to_string() }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.constraint {
+            Some((comparison, ref version)) => write!(f, "{} {comparison} {version}", self.tool),
+            None => f.write_str(&self.tool),
+        }
+    }
+}
+
+/// Details about a tool named in the `requirements` of a plugin, given in the `tools`
+/// field of the plugin.
+#[derive(
+    Clone, Debug, Default, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq,
+)]
+pub struct Tool {
+    /// The command and arguments that print the version of the tool, such as
+    /// `("go", "version")`. The arguments are split like those of a `ShellCommand`.
+    /// Defaults to running the tool with `--version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version_command: Option<(String, String)>,
+    /// Tells the user how to install the tool if it's missing or too old.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    install_hint:    Option<String>,
+}
+
+/// Why a [`Requirement`] isn't met.
+#[derive(Clone, Debug)]
+pub enum Problem {
+    /// The tool couldn't be found on `PATH`.
+    Missing,
+    /// The version of the tool couldn't be worked out.
+    UnknownVersion(String),
+    /// The tool was found, but its version doesn't satisfy the constraint.
+    WrongVersion(Version),
+}
+
+/// A [`Requirement`] of a plugin that isn't met, along with how to fix it.
+#[derive(Clone, Debug)]
+pub struct UnmetRequirement {
+    pub requirement:  Requirement,
+    pub problem:      Problem,
+    pub install_hint: Option<String>,
+}
+
+impl fmt::Display for UnmetRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.problem {
+            Problem::Missing => write!(f, "{}: not found on PATH", self.requirement)?,
+            Problem::UnknownVersion(ref reason) => write!(
+                f,
+                "{}: could not find out the installed version ({reason})",
+                self.requirement
+            )?,
+            Problem::WrongVersion(ref version) =>
+                write!(f, "{}: found version {version}", self.requirement)?,
+        }
+
+        if let Some(ref hint) = self.install_hint {
+            write!(f, ". {hint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks every one of `requirements` against the commands available in `environment`,
+/// returning those that aren't met. Version commands are killed if `cancelled` is set.
+/// With `dry_run`, only whether the tools can be found is checked, as version commands
+/// come from the plugin and a dry run doesn't run anything.
+pub(super) fn unmet_requirements(
+    requirements: &[Requirement],
+    tools: &BTreeMap<String, Tool>,
+    environment: &CommandEnvironment,
+    cancelled: &AtomicBool,
+    dry_run: bool,
+) -> Vec<UnmetRequirement> {
+    let search_path = environment.search_path();
+
+    requirements
+        .iter()
+        .filter_map(|requirement| {
+            let tool = tools.get(&requirement.tool).cloned().unwrap_or_default();
+            let problem = check(
+                requirement,
+                &tool,
+                &search_path,
+                environment,
+                cancelled,
+                dry_run,
+            )?;
+
+            Some(UnmetRequirement {
+                requirement: requirement.clone(),
+                problem,
+                install_hint: tool.install_hint,
+            })
+        })
+        .collect()
+}
+
+/// Checks a single requirement, returning what's wrong with it if it isn't met.
+fn check(
+    requirement: &Requirement,
+    tool: &Tool,
+    search_path: &[PathBuf],
+    environment: &CommandEnvironment,
+    cancelled: &AtomicBool,
+    dry_run: bool,
+) -> Option<Problem> {
+    let Some(executable) = find_executable(&requirement.tool, search_path) else {
+        return Some(Problem::Missing);
+    };
+    let (comparison, wanted) = requirement.constraint.as_ref()?;
+
+    if dry_run {
+        return None;
+    }
+
+    let (command, arguments) = version_command(requirement, tool, Some(&executable));
+
+    let mut output = Vec::new();
+    let status = run_command(
+        &command,
+        &arguments,
+        environment,
+        Some(VERSION_COMMAND_TIMEOUT),
+        cancelled,
+        |line| output.push(line),
+    );
+
+    let found = match status {
+        Ok(status) if !status.success() =>
+            return Some(Problem::UnknownVersion(format!(
+                "`{command}` exited with {status}"
+            ))),
+        Ok(_) => Version::find_in(&output.join("\n")),
+        Err(error) => return Some(Problem::UnknownVersion(error.to_string())),
+    };
+
+    match found {
+        Some(found) if comparison.holds(found.cmp(wanted)) => None,
+        Some(found) => Some(Problem::WrongVersion(found)),
+        None => Some(Problem::UnknownVersion(format!(
+            "`{command}` didn't print a version number"
+        ))),
+    }
+}
+
+/// The command and arguments that print the version of the tool of `requirement`: the
+/// `version_command` of `tool`, or else the tool with `--version`. The tool is run from
+/// `executable` if it's known where it is.
+pub(super) fn version_command(
+    requirement: &Requirement,
+    tool: &Tool,
+    executable: Option<&Path>,
+) -> (String, Vec<String>) {
+    match tool.version_command {
+        Some((ref command, ref arguments)) => (command.clone(), split_arguments(arguments)),
+        None => (
+            executable.map_or_else(
+                || requirement.tool.clone(),
+                |executable| executable.to_string_lossy().into_owned(),
+            ),
+            vec![String::from("--version")],
+        ),
+    }
+}
+
+/// Looks for an executable called `tool` in the directories of `search_path`, returning
+/// where it was found.
+fn find_executable(tool: &str, search_path: &[PathBuf]) -> Option<PathBuf> {
+    if Path::new(tool).components().count() > 1 {
+        return is_executable(Path::new(tool)).then(|| PathBuf::from(tool));
+    }
+
+    search_path
+        .iter()
+        .flat_map(|dir| candidates(&dir.join(tool)))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn candidates(path: &Path) -> Vec<PathBuf> { vec![path.to_owned()] }
+
+#[cfg(windows)]
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| String::from(".EXE;.CMD;.BAT"));
+
+    std::iter::once(path.to_owned())
+        .chain(extensions.split(';').map(|extension| {
+            let mut candidate = path.as_os_str().to_owned();
+            candidate.push(extension);
+            PathBuf::from(candidate)
+        }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version { version.parse().unwrap() }
+
+    fn requirement(requirement: &str) -> Result<Requirement, String> {
+        Requirement::try_from(requirement.to_owned())
+    }
+
+    #[test]
+    fn finds_the_version_in_version_output() {
+        let found = |output| Version::find_in(output).map(|version| version.to_string());
+
+        assert_eq!(
+            found("go version go1.21.5 linux/amd64").as_deref(),
+            Some("1.21.5")
+        );
+        assert_eq!(
+            found("cargo 1.75.0 (1d8b05cdd 2023-11-20)").as_deref(),
+            Some("1.75.0")
+        );
+        assert_eq!(found("v2 of tool 1.4").as_deref(), Some("1.4"));
+        assert_eq!(found("tool 3").as_deref(), Some("3"));
+        assert_eq!(found("no version here"), None);
+    }
+
+    #[test]
+    fn treats_missing_version_components_as_zero() {
+        assert_eq!(version("1.21"), version("1.21.0"));
+        assert_eq!(version("1"), version("1.0.0"));
+        assert!(version("1.2") < version("1.10"));
+        assert!(version("1") < version("1.0.1"));
+        assert!(version("2") > version("1.99.99"));
+        assert!("1.x".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn parses_requirements() {
+        let go = requirement("go >= 1.21").unwrap();
+        assert_eq!(go.tool(), "go");
+        assert_eq!(
+            go.constraint(),
+            Some(&(Comparison::GreaterOrEqual, version("1.21")))
+        );
+
+        let go = requirement("go==1.2").unwrap();
+        assert_eq!(go.tool(), "go");
+        assert_eq!(go.constraint(), Some(&(Comparison::Equal, version("1.2"))));
+
+        let cargo = requirement(" cargo ").unwrap();
+        assert_eq!(cargo.tool(), "cargo");
+        assert_eq!(cargo.constraint(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_requirements() {
+        assert!(requirement(">= 1").is_err());
+        assert!(requirement("go ~ 1").is_err());
+        assert!(requirement("go >= one").is_err());
+        assert!(requirement("").is_err());
+    }
+}
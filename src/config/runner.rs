@@ -0,0 +1,2355 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fmt, fs,
+    path::{Component, Path, PathBuf},
+    process::ExitStatus,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use heck::{ToKebabCase, ToSnakeCase};
+
+use super::{
+    make_executable,
+    process::run_command,
+    requirement::{unmet_requirements, version_command},
+    Answers, CommandEnvironment, CommandStep, CommandType, Effect, FailurePolicy, Frontend,
+    GivenAnswer, Journal, LanguageConfig, PluginSource, ProjectType, Question, Requirement,
+    Template, UnmetRequirement, Validation, Variables, TEMPLATE_DIR, TEMPLATE_EXTENSION,
+};
+
+/// The choices given to the user when a run is stopped after it has changed the
+/// filesystem.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+enum RollbackAction {
+    CleanUp,
+    Keep,
+}
+
+impl fmt::Display for RollbackAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CleanUp => "Clean up",
+            Self::Keep => "Keep partial project",
+        })
+    }
+}
+
+/// The choices given to the user when a step with [`FailurePolicy::Ask`] fails.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+enum FailureAction {
+    Retry,
+    Skip,
+    Abort,
+}
+
+impl fmt::Display for FailureAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Retry => "Retry",
+            Self::Skip => "Skip",
+            Self::Abort => "Abort",
+        })
+    }
+}
+
+/// An action that a step would take, reported instead of being carried out when the
+/// runner is in dry-run mode.
+#[derive(Clone, Debug)]
+pub enum PlannedAction {
+    RunCommand {
+        command:     String,
+        arguments:   Vec<String>,
+        environment: CommandEnvironment,
+    },
+    WriteFile {
+        path:  PathBuf,
+        bytes: usize,
+    },
+    /// Checking the version of a tool for a requirement, which isn't done in a dry run as
+    /// it would run the plugin's version command.
+    CheckVersion {
+        requirement: Requirement,
+        command:     String,
+        arguments:   Vec<String>,
+    },
+    /// A requirement that isn't met, which would stop the run before its first step. A
+    /// dry run carries on so that the rest of the plan can still be seen.
+    UnmetRequirement(UnmetRequirement),
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RunCommand {
+                command,
+                arguments,
+                environment,
+            } => {
+                f.write_str("run ")?;
+                write_command(f, command, arguments)?;
+                write!(f, " {environment}")
+            },
+            Self::WriteFile { path, bytes } =>
+                write!(f, "write {} ({bytes} bytes)", path.display()),
+            Self::CheckVersion {
+                requirement,
+                command,
+                arguments,
+            } => {
+                write!(f, "check {requirement} with ")?;
+                write_command(f, command, arguments)?;
+                f.write_str(": not checked (dry run)")
+            },
+            Self::UnmetRequirement(unmet) =>
+                write!(f, "warning: {unmet} (the run would stop here)"),
+        }
+    }
+}
+
+/// Writes a command and its arguments in backticks, quoting the arguments that are empty
+/// or contain whitespace.
+fn write_command(f: &mut fmt::Formatter<'_>, command: &str, arguments: &[String]) -> fmt::Result {
+    write!(f, "`{command}")?;
+
+    for argument in arguments {
+        if argument.is_empty() || argument.contains(char::is_whitespace) {
+            write!(f, " \"{argument}\"")?;
+        } else {
+            write!(f, " {argument}")?;
+        }
+    }
+
+    f.write_str("`")
+}
+
+/// The progress of a run, shown through [`Frontend::show`].
+#[derive(Clone, Debug)]
+pub enum RunningConfigMessage {
+    SetCommandStepText(String),
+    StepSkipped(String),
+    /// A prompt wasn't asked, because it was answered before the run.
+    AlreadyAnswered {
+        label:  String,
+        answer: String,
+    },
+    CommandOutput(String),
+    CommandExited(ExitStatus),
+    Planned(PlannedAction),
+    StepFailed(RunnerError),
+    /// The changes that the run made before it was stopped, shown before asking whether
+    /// they should be cleaned up.
+    Effects(Vec<Effect>),
+    Failed(RunnerError),
+    /// The run has finished, whether it succeeded or not.
+    Finished,
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum RunnerError {
+    #[error("could not start \"{command}\": {reason}")]
+    CouldNotSpawnCommand { command: String, reason: String },
+    #[error("could not resolve the placeholders in \"{template}\": {reason}")]
+    CouldNotInterpolate { template: String, reason: String },
+    #[error("could not create the directory \"{path}\": {reason}")]
+    CouldNotCreateDirectory { path: PathBuf, reason: String },
+    #[error("could not read the template \"{path}\": {reason}")]
+    CouldNotReadTemplate { path: PathBuf, reason: String },
+    #[error("could not render the template \"{template}\": {reason}")]
+    CouldNotRenderTemplate { template: String, reason: String },
+    #[error("could not write the file \"{path}\": {reason}")]
+    CouldNotWriteFile { path: PathBuf, reason: String },
+    #[error("\"{command}\" did not finish within {seconds} seconds")]
+    TimedOut { command: String, seconds: u64 },
+    #[error("the prompt for \"{variable}\" is invalid: {reason}")]
+    InvalidPrompt { variable: String, reason: String },
+    #[error("\"{answer}\" can't be used: {reason}")]
+    InvalidAnswer { answer: String, reason: String },
+    #[error("no answer was given for \"{variable}\" ({label})")]
+    MissingAnswer { variable: String, label: String },
+    #[error("the tools this plugin requires aren't all installed")]
+    UnmetRequirements(Vec<UnmetRequirement>),
+    #[error("no project made with this plugin was found in \"{0}\"")]
+    ProjectNotFound(PathBuf),
+    #[error("the plugin can't add members to existing projects")]
+    CannotAddMembers,
+    #[error("could not find the current directory to create the project in: {0}")]
+    NoCurrentDirectory(String),
+    #[error("the run was cancelled")]
+    Cancelled,
+    #[error("\"{command}\" failed ({status})")]
+    CommandFailed {
+        command: String,
+        status:  ExitStatus,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct LanguageConfigRunner {
+    config:           LanguageConfig,
+    project_name:     Arc<RwLock<String>>,
+    project_type:     Arc<RwLock<ProjectType>>,
+    dry_run:          bool,
+    /// Answers given before the run, by the variable of the prompt they answer.
+    answers:          Answers,
+    interactive:      bool,
+    /// The answers to the prompts of the run so far, whether they were asked or given.
+    collected:        Arc<RwLock<Answers>>,
+    /// The directory of the existing project that members are added to, if the run adds
+    /// to a project rather than creating one.
+    existing_project: Option<PathBuf>,
+    /// The directory the project is created in, which is the current directory if it
+    /// isn't given.
+    base_dir:         Option<PathBuf>,
+    cancelled:        Arc<AtomicBool>,
+}
+
+impl LanguageConfigRunner {
+    pub(super) fn new(config: LanguageConfig) -> LanguageConfigRunner {
+        LanguageConfigRunner {
+            config,
+            project_name: Arc::new(RwLock::new(String::new())),
+            project_type: Arc::new(RwLock::new(ProjectType::Binary)),
+            dry_run: false,
+            answers: Answers::new(),
+            interactive: true,
+            collected: Arc::new(RwLock::new(Answers::new())),
+            existing_project: None,
+            base_dir: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Answers prompts before the run. A prompt that has been answered isn't asked;
+    /// instead, the answer is parsed as described by [`Question::parse`] and shown with
+    /// [`RunningConfigMessage::AlreadyAnswered`], and the run fails if it can't be used.
+    pub fn with_answers(mut self, answers: Answers) -> Self {
+        self.answers = answers;
+        self
+    }
+
+    /// Sets whether prompts that weren't answered with [`Self::with_answers`] are asked.
+    /// If not, the run fails with [`RunnerError::MissingAnswer`] at the first one. The
+    /// frontend is still asked what to do when a step fails or the run is stopped.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Sets whether the runner is in dry-run mode. In dry-run mode the prompts are still
+    /// asked, but rather than running any commands the runner reports what it would have
+    /// done with [`RunningConfigMessage::Planned`]. Requirements that aren't met are
+    /// reported in the same way, rather than stopping the run.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Adds members to the existing project that `dir` is in, with the plugin's
+    /// `add_member` steps, rather than creating a new project. The root of the project is
+    /// found with [`LanguageConfig::find_project`], and the run fails with
+    /// [`RunnerError::ProjectNotFound`] if there isn't one.
+    pub fn with_existing_project(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.existing_project = Some(dir.into());
+        self
+    }
+
+    /// Creates the project in `dir`, rather than in the current directory. Relative
+    /// paths in the plugin, such as the `cwd` of steps, are relative to it.
+    pub fn with_base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+
+    pub fn config(&self) -> &LanguageConfig { &self.config }
+
+    /// The answers given before the run with [`Self::with_answers`].
+    pub fn answers(&self) -> &Answers { &self.answers }
+
+    /// The answers to the prompts of the run so far, whether they were asked or given
+    /// before the run. These can be saved, to give them again next time.
+    pub fn collected_answers(&self) -> Answers { self.collected.read().unwrap().clone() }
+
+    /// The name given to the project, once it has been asked for.
+    pub fn project_name(&self) -> String { self.project_name.read().unwrap().clone() }
+
+    /// The type chosen for the project, once it has been asked for.
+    pub fn project_type(&self) -> ProjectType { self.project_type.read().unwrap().clone() }
+
+    /// Cancels the run, killing the command that is currently running along with any
+    /// processes it started. The run is then stopped as if the step had failed with
+    /// [`FailurePolicy::Abort`]. Clones of the runner share the same run, so a clone can
+    /// be used to cancel a run that is in progress on another thread.
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::SeqCst); }
+
+    /// Runs each of the steps of the plugin in the base directory, see
+    /// [`Self::with_base_dir`], asking questions and showing progress through `frontend`.
+    /// Blocks until the run has finished, returning why it failed if it did.
+    pub fn run(&self, frontend: &mut dyn Frontend) -> std::result::Result<(), RunnerError> {
+        let base_dir = match self.base_dir {
+            Some(ref dir) => Ok(dir.clone()),
+            None => env::current_dir()
+                .map_err(|error| RunnerError::NoCurrentDirectory(error.to_string())),
+        };
+        let base_dir = match base_dir {
+            Ok(base_dir) => base_dir,
+            Err(error) => {
+                frontend.show(RunningConfigMessage::Failed(error.clone()));
+                frontend.show(RunningConfigMessage::Finished);
+
+                return Err(error);
+            },
+        };
+        let mut context = RunContext {
+            frontend,
+            config: self.config.clone(),
+            name_lock: self.project_name.clone(),
+            type_lock: self.project_type.clone(),
+            variables: Variables::with_builtins(),
+            // a dry run doesn't change anything, so there is nothing to keep track of.
+            journal: (!self.dry_run).then(|| Journal::new(&base_dir)),
+            base_dir,
+            dry_run: self.dry_run,
+            answers: self.answers.clone(),
+            interactive: self.interactive,
+            collected: self.collected.clone(),
+            existing_project: self.existing_project.clone(),
+            cancelled: self.cancelled.clone(),
+        };
+
+        let result = context.run();
+
+        if let Err(ref error) = result {
+            context.show(RunningConfigMessage::Failed(error.clone()));
+        }
+
+        context.show(RunningConfigMessage::Finished);
+
+        result
+    }
+}
+
+/// The state of a run that is in progress, made by [`LanguageConfigRunner::run`].
+struct RunContext<'a> {
+    frontend:         &'a mut dyn Frontend,
+    config:           LanguageConfig,
+    name_lock:        Arc<RwLock<String>>,
+    type_lock:        Arc<RwLock<ProjectType>>,
+    variables:        Variables,
+    /// What the run has changed, so that it can be undone. There is none in a dry run.
+    journal:          Option<Journal>,
+    /// The directory `proman` was run in, which relative paths are resolved against.
+    base_dir:         PathBuf,
+    dry_run:          bool,
+    answers:          Answers,
+    interactive:      bool,
+    collected:        Arc<RwLock<Answers>>,
+    existing_project: Option<PathBuf>,
+    cancelled:        Arc<AtomicBool>,
+}
+
+impl RunContext<'_> {
+    fn show(&mut self, message: RunningConfigMessage) { self.frontend.show(message); }
+
+    fn run(&mut self) -> std::result::Result<(), RunnerError> {
+        let commands = self.config.command_steps().to_vec();
+
+        // the requirements are checked before anything is asked, so the plugin's
+        // environment can only use the built-in variables.
+        let environment = self.plugin_environment()?;
+        let unmet = unmet_requirements(
+            self.config.requirements(),
+            self.config.tools(),
+            &environment,
+            &self.cancelled,
+            self.dry_run,
+        );
+
+        if self.dry_run {
+            for unmet in unmet {
+                self.show(RunningConfigMessage::Planned(
+                    PlannedAction::UnmetRequirement(unmet),
+                ));
+            }
+
+            self.plan_version_checks();
+        } else if !unmet.is_empty() {
+            return Err(RunnerError::UnmetRequirements(unmet));
+        }
+
+        let result = match self.existing_project.clone() {
+            Some(dir) => self.add_to_project(&dir),
+            None => self.run_steps(&commands),
+        };
+
+        if result.is_err() {
+            self.offer_rollback();
+        }
+
+        result
+    }
+
+    /// Reports the version checks of the requirements that a dry run skips.
+    fn plan_version_checks(&mut self) {
+        let requirements = self.config.requirements().to_vec();
+
+        for requirement in requirements {
+            if requirement.constraint().is_none() {
+                continue;
+            }
+
+            let tool = self
+                .config
+                .tools()
+                .get(requirement.tool())
+                .cloned()
+                .unwrap_or_default();
+            let (command, arguments) = version_command(&requirement, &tool, None);
+
+            self.show(RunningConfigMessage::Planned(PlannedAction::CheckVersion {
+                requirement,
+                command,
+                arguments,
+            }));
+        }
+    }
+
+    /// Runs each of `steps` in turn, skipping those whose conditions don't hold and
+    /// handling failures according to the [`FailurePolicy`] of each step. Returns the
+    /// error that stopped the steps, if any did.
+    fn run_steps(&mut self, steps: &[CommandStep]) -> std::result::Result<(), RunnerError> {
+        for step in steps {
+            if self.cancelled.load(Ordering::SeqCst) {
+                return Err(RunnerError::Cancelled);
+            }
+
+            if let Some(condition) = step.when() {
+                if !condition.evaluate(&self.variables)? {
+                    self.show(RunningConfigMessage::StepSkipped(step.name().to_owned()));
+                    continue;
+                }
+            }
+
+            let mut attempts = 0;
+
+            loop {
+                self.show(RunningConfigMessage::SetCommandStepText(
+                    step.name().to_owned(),
+                ));
+
+                let Err(error) = self.run_step(step) else {
+                    break;
+                };
+
+                // the steps of each member handle their own failures, so whatever stopped
+                // them has already been dealt with.
+                if matches!(step.command(), CommandType::CreateMembers) {
+                    return Err(error);
+                }
+
+                match self.failure_action(step.on_failure(), &error, attempts) {
+                    FailureAction::Retry => attempts += 1,
+                    FailureAction::Skip => break,
+                    FailureAction::Abort => return Err(error),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asks the user whether the filesystem effects of the run so far should be undone,
+    /// undoing them if so. Nothing is asked if the run hasn't changed anything.
+    fn offer_rollback(&mut self) {
+        let Some(effects) = self.journal.as_ref().map(Journal::effects) else {
+            return;
+        };
+
+        if effects.is_empty() {
+            return;
+        }
+
+        self.show(RunningConfigMessage::Effects(effects));
+
+        let actions = [RollbackAction::CleanUp, RollbackAction::Keep];
+        let options = actions.map(|action| action.to_string());
+        let action = self
+            .frontend
+            .ask_choice("The run stopped, clean up what it did?", &options, 0)
+            .map_or(RollbackAction::Keep, |index| actions[index]);
+
+        if action != RollbackAction::CleanUp {
+            return;
+        }
+
+        let Some(ref journal) = self.journal else {
+            return;
+        };
+
+        match journal.rollback() {
+            Ok(effects) =>
+                for effect in effects {
+                    self.show(RunningConfigMessage::CommandOutput(format!(
+                        "Undid: {effect}"
+                    )));
+                },
+            Err(error) => self.show(RunningConfigMessage::CommandOutput(format!(
+                "Could not clean up: {error}"
+            ))),
+        }
+    }
+
+    /// Decides what to do about a step that failed with `error`, according to the
+    /// step's [`FailurePolicy`] and how many times it has already been retried.
+    fn failure_action(
+        &mut self,
+        policy: &FailurePolicy,
+        error: &RunnerError,
+        attempts: u32,
+    ) -> FailureAction {
+        self.show(RunningConfigMessage::StepFailed(error.clone()));
+
+        if matches!(error, RunnerError::Cancelled) {
+            return FailureAction::Abort;
+        }
+
+        match policy {
+            FailurePolicy::Abort => FailureAction::Abort,
+            FailurePolicy::Retry(retries) if attempts < *retries => FailureAction::Retry,
+            FailurePolicy::Retry(_) => FailureAction::Abort,
+            FailurePolicy::Skip => FailureAction::Skip,
+            FailurePolicy::Ask => {
+                let actions = [
+                    FailureAction::Retry,
+                    FailureAction::Skip,
+                    FailureAction::Abort,
+                ];
+                let options = actions.map(|action| action.to_string());
+
+                self.frontend
+                    .ask_choice("The step failed, what should happen?", &options, 0)
+                    .map_or(FailureAction::Abort, |index| actions[index])
+            },
+        }
+    }
+
+    /// Resolves the working directory and environment variables for `step`, combining
+    /// those given by the step with the defaults given by the plugin.
+    fn resolve_environment(
+        &self,
+        step: &CommandStep,
+    ) -> std::result::Result<CommandEnvironment, RunnerError> {
+        let mut environment = self.plugin_environment()?;
+
+        if let Some(cwd) = step.cwd() {
+            environment.working_dir = self.base_dir.join(self.variables.interpolate(cwd)?);
+        }
+
+        for (name, value) in step.env() {
+            environment
+                .env
+                .insert(name.clone(), self.variables.interpolate(value)?);
+        }
+
+        Ok(environment)
+    }
+
+    /// Resolves the environment that the plugin sets for every step, run in the directory
+    /// `proman` was run in. Fails if it uses a variable that hasn't been set yet.
+    fn plugin_environment(&self) -> std::result::Result<CommandEnvironment, RunnerError> {
+        let mut env = BTreeMap::new();
+        for (name, value) in self.config.env() {
+            env.insert(name.clone(), self.variables.interpolate(value)?);
+        }
+
+        let path = self
+            .config
+            .path()
+            .iter()
+            .map(|dir| Ok(self.base_dir.join(self.variables.interpolate(dir)?)))
+            .collect::<std::result::Result<Vec<_>, RunnerError>>()?;
+
+        Ok(CommandEnvironment {
+            working_dir: self.base_dir.clone(),
+            env,
+            path,
+        })
+    }
+
+    /// Creates the working directory of a step if it doesn't exist yet, recording the
+    /// directories it creates in the journal, or making sure that the journal is watching
+    /// it if it already exists.
+    fn create_working_dir(&mut self, working_dir: &Path) -> std::result::Result<(), RunnerError> {
+        if working_dir.is_dir() {
+            if let Some(ref mut journal) = self.journal {
+                journal.watch(working_dir, false);
+            }
+            return Ok(());
+        }
+
+        if let (Some(journal), Some(outermost)) = (
+            self.journal.as_mut(),
+            working_dir
+                .ancestors()
+                .take_while(|dir| !dir.exists())
+                .last(),
+        ) {
+            journal.creating(outermost);
+        }
+
+        fs::create_dir_all(working_dir).map_err(|error| RunnerError::CouldNotCreateDirectory {
+            path:   working_dir.to_owned(),
+            reason: error.to_string(),
+        })
+    }
+
+    fn run_step(&mut self, step: &CommandStep) -> std::result::Result<(), RunnerError> {
+        match step.command() {
+            CommandType::PromptProjectName => {
+                let question = Question::Text {
+                    label:      String::from("Project name"),
+                    default:    None,
+                    validation: step.validation().cloned().unwrap_or_default(),
+                };
+                let name = self.ask("project_name", &question)?.to_string();
+                check_relative_path(Path::new(&name)).map_err(|reason| {
+                    RunnerError::InvalidAnswer {
+                        answer: name.clone(),
+                        reason,
+                    }
+                })?;
+
+                let project_dir = self.base_dir.join(&name).display().to_string();
+
+                // the steps are expected to create the project directory, so if it doesn't
+                // exist yet, everything in it is theirs.
+                if let Some(ref mut journal) = self.journal {
+                    journal.watch(Path::new(&project_dir), true);
+                    journal.creating(Path::new(&project_dir));
+                }
+                self.variables.set("project_name", name.clone());
+                self.variables
+                    .set("project_name_snake_case", name.to_snake_case());
+                self.variables
+                    .set("project_name_kebab_case", name.to_kebab_case());
+                self.variables.set("project_dir", project_dir);
+                *self.name_lock.write().unwrap() = name;
+            },
+            CommandType::PromptProjectType => {
+                let types = self
+                    .config
+                    .project_types()
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let question = Question::Choice {
+                    label:   String::from("Project type"),
+                    options: types.iter().map(ToString::to_string).collect(),
+                    default: 0,
+                };
+
+                // there's nothing to choose between if the plugin only has one type, unless
+                // a type was given anyway, which still has to be one of the plugin's.
+                let project_type = match types.len() {
+                    0 => None,
+                    1 if self.answers.get("project_type").is_none() => types.first().cloned(),
+                    _ => {
+                        let chosen = self.ask("project_type", &question)?.to_string();
+
+                        types
+                            .iter()
+                            .find(|project_type| project_type.to_string() == chosen)
+                            .cloned()
+                    },
+                };
+
+                if let Some(project_type) = project_type {
+                    self.variables.set("project_type", project_type.to_string());
+                    *self.type_lock.write().unwrap() = project_type;
+                }
+            },
+            CommandType::PromptText { variable, .. }
+            | CommandType::PromptChoice { variable, .. }
+            | CommandType::PromptConfirm { variable, .. }
+            | CommandType::PromptMultiSelect { variable, .. } => {
+                let question = self.question(step)?;
+                let value = self.ask(variable, &question)?;
+
+                self.variables.set(variable, value);
+            },
+            CommandType::Command(command, arguments) => {
+                let (command, arguments) = resolve_command(command, arguments, &self.variables)?;
+                let environment = self.resolve_environment(step)?;
+
+                if self.dry_run {
+                    self.show(RunningConfigMessage::Planned(PlannedAction::RunCommand {
+                        command,
+                        arguments,
+                        environment,
+                    }));
+
+                    return Ok(());
+                }
+
+                self.create_working_dir(&environment.working_dir)?;
+
+                let timeout = step.timeout().map(Duration::from_secs);
+                let status = run_command(
+                    &command,
+                    &arguments,
+                    &environment,
+                    timeout,
+                    &self.cancelled,
+                    |line| {
+                        self.frontend
+                            .show(RunningConfigMessage::CommandOutput(line))
+                    },
+                )?;
+
+                self.show(RunningConfigMessage::CommandExited(status));
+
+                if !status.success() {
+                    return Err(RunnerError::CommandFailed { command, status });
+                }
+            },
+            CommandType::WriteFile { path, template } => {
+                let dir = self.output_dir(step)?;
+                let path = self.variables.interpolate(path)?;
+                let path = check_relative_path(Path::new(&path))
+                    .map(|()| dir.join(&path))
+                    .map_err(|reason| RunnerError::CouldNotWriteFile {
+                        path: dir.join(&path),
+                        reason,
+                    })?;
+                let (name, template) = self.read_template(step, template)?;
+                let contents = self.variables.render(&name, &template)?;
+
+                self.write_file(&path, contents.as_bytes(), false)?;
+            },
+            CommandType::CreateMembers => {
+                let validation = step.validation().cloned().unwrap_or_default();
+                let steps = self.config.member_steps().to_vec();
+
+                self.create_members(&validation, &steps)?;
+            },
+            CommandType::CopyTemplate => {
+                let dir = self.output_dir(step)?;
+                let could_not_read = |reason: String| RunnerError::CouldNotReadTemplate {
+                    path: PathBuf::from(TEMPLATE_DIR),
+                    reason,
+                };
+
+                let files = match self.plugin_source(step) {
+                    Some(source) => source
+                        .files(Path::new(TEMPLATE_DIR))
+                        .map_err(|error| could_not_read(error.to_string()))?,
+                    None => Vec::new(),
+                };
+
+                if files.is_empty() {
+                    return Err(could_not_read(String::from(
+                        "the plugin has no files in its template directory",
+                    )));
+                }
+
+                for file in files {
+                    let Some(mut path) = self.render_path(&file.path)? else {
+                        continue;
+                    };
+                    check_relative_path(&path).map_err(|reason| {
+                        RunnerError::CouldNotWriteFile {
+                            path: dir.join(&path),
+                            reason,
+                        }
+                    })?;
+
+                    // only the files that ask to be rendered are, so that files that
+                    // use the same syntax for something else are copied as they are.
+                    let contents = if path
+                        .extension()
+                        .is_some_and(|extension| extension == TEMPLATE_EXTENSION)
+                    {
+                        path.set_extension("");
+
+                        let template = String::from_utf8(file.contents).map_err(|error| {
+                            RunnerError::CouldNotReadTemplate {
+                                path:   Path::new(TEMPLATE_DIR).join(&file.path),
+                                reason: error.to_string(),
+                            }
+                        })?;
+
+                        self.variables
+                            .render(&file.path.display().to_string(), &template)?
+                            .into_bytes()
+                    } else {
+                        file.contents
+                    };
+
+                    self.write_file(&dir.join(path), &contents, file.executable)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Answers `question` with the answer given for `variable` before the run, or asks it
+    /// through the frontend if there isn't one. The answer is checked in case the
+    /// frontend didn't, and the value of the answer is returned.
+    fn ask(
+        &mut self,
+        variable: &str,
+        question: &Question,
+    ) -> std::result::Result<minijinja::Value, RunnerError> {
+        let answer = match self.answers.get(variable) {
+            Some(given) => {
+                let answer =
+                    question
+                        .parse(given)
+                        .map_err(|reason| RunnerError::InvalidAnswer {
+                            answer: given.to_string(),
+                            reason,
+                        })?;
+
+                self.show(RunningConfigMessage::AlreadyAnswered {
+                    label:  question.label().to_owned(),
+                    answer: given.to_string(),
+                });
+
+                answer
+            },
+            None if !self.interactive =>
+                return Err(RunnerError::MissingAnswer {
+                    variable: variable.to_owned(),
+                    label:    question.label().to_owned(),
+                }),
+            None => question
+                .ask(&mut *self.frontend)
+                .ok_or(RunnerError::Cancelled)?,
+        };
+
+        question
+            .check(&answer)
+            .map_err(|reason| RunnerError::InvalidAnswer {
+                answer: question.value_of(answer.clone()).to_string(),
+                reason,
+            })?;
+
+        let value = question.value_of(answer);
+        self.collected
+            .write()
+            .unwrap()
+            .insert(variable, GivenAnswer::from(&value));
+
+        Ok(value)
+    }
+
+    /// Adds members to the existing project that `dir` is in, running the plugin's
+    /// `add_member` steps for each. The project's variables are set from its root, and
+    /// paths are resolved against the directory containing it, so that the steps can be
+    /// written in the same way as the plugin's member steps. Member names are checked
+    /// against the `validation` of the plugin's `CreateMembers` step, if it has one.
+    fn add_to_project(&mut self, dir: &Path) -> std::result::Result<(), RunnerError> {
+        if self.config.add_member().is_empty() {
+            return Err(RunnerError::CannotAddMembers);
+        }
+
+        let root = self
+            .config
+            .find_project(dir)
+            .ok_or_else(|| RunnerError::ProjectNotFound(dir.to_owned()))?;
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.base_dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+        if !self.dry_run {
+            let mut journal = Journal::new(&self.base_dir);
+            journal.watch(&root, false);
+            self.journal = Some(journal);
+        }
+
+        self.variables.set("project_name", name.clone());
+        self.variables
+            .set("project_name_snake_case", name.to_snake_case());
+        self.variables
+            .set("project_name_kebab_case", name.to_kebab_case());
+        self.variables
+            .set("project_dir", root.display().to_string());
+        *self.name_lock.write().unwrap() = name;
+
+        if self
+            .config
+            .project_types()
+            .contains(&ProjectType::Workspace)
+        {
+            self.variables
+                .set("project_type", ProjectType::Workspace.to_string());
+            *self.type_lock.write().unwrap() = ProjectType::Workspace;
+        }
+
+        let validation = self
+            .config
+            .command_steps()
+            .iter()
+            .find(|step| matches!(step.command(), CommandType::CreateMembers))
+            .and_then(CommandStep::validation)
+            .cloned()
+            .unwrap_or_default();
+        let steps = self.config.add_member().to_vec();
+
+        self.show(RunningConfigMessage::SetCommandStepText(format!(
+            "Adding members to {}",
+            root.display()
+        )));
+
+        self.create_members(&validation, &steps)
+    }
+
+    /// Adds the members of a workspace, running `steps` for each. The members are taken
+    /// from the `members` answer if it was given before the run, as a list of `name` or
+    /// `name:type`; otherwise, they're asked for one at a time. The names of members are
+    /// checked against `validation`.
+    fn create_members(
+        &mut self,
+        validation: &Validation,
+        steps: &[CommandStep],
+    ) -> std::result::Result<(), RunnerError> {
+        let types = self
+            .config
+            .project_types()
+            .iter()
+            .filter(|project_type| **project_type != ProjectType::Workspace)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if types.is_empty() {
+            return Err(RunnerError::InvalidPrompt {
+                variable: String::from("members"),
+                reason:   String::from("the plugin has no project types for members"),
+            });
+        }
+
+        let mut members = Vec::new();
+
+        match self.answers.get("members").cloned() {
+            Some(given) => {
+                let entries = match given {
+                    GivenAnswer::List(ref entries) => entries.clone(),
+                    GivenAnswer::Text(ref text) => text
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|entry| !entry.is_empty())
+                        .map(str::to_owned)
+                        .collect(),
+                    GivenAnswer::Bool(_) =>
+                        return Err(RunnerError::InvalidAnswer {
+                            answer: given.to_string(),
+                            reason: String::from("the answer has to be a list of members"),
+                        }),
+                };
+
+                self.show(RunningConfigMessage::AlreadyAnswered {
+                    label:  String::from("Workspace members"),
+                    answer: given.to_string(),
+                });
+
+                for entry in entries {
+                    let (name, member_type) = parse_member(&entry, &types, validation)?;
+
+                    self.add_member(&name, &member_type, steps, &mut members)?;
+                }
+            },
+            None if !self.interactive =>
+                return Err(RunnerError::MissingAnswer {
+                    variable: String::from("members"),
+                    label:    String::from("Workspace members"),
+                }),
+            None => loop {
+                let name = self
+                    .frontend
+                    .ask_text("Member name", None, validation)
+                    .ok_or(RunnerError::Cancelled)?;
+                validation
+                    .check(&name)
+                    .map_err(|reason| RunnerError::InvalidAnswer {
+                        answer: name.clone(),
+                        reason,
+                    })?;
+
+                let member_type = match types.as_slice() {
+                    [only] => only.clone(),
+                    _ => {
+                        let options = types.iter().map(ToString::to_string).collect::<Vec<_>>();
+                        let index = self
+                            .frontend
+                            .ask_choice("Member type", &options, 0)
+                            .ok_or(RunnerError::Cancelled)?;
+
+                        types[index].clone()
+                    },
+                };
+
+                self.add_member(&name, &member_type, steps, &mut members)?;
+
+                let another = self
+                    .frontend
+                    .ask_confirm("Add another member?", false)
+                    .ok_or(RunnerError::Cancelled)?;
+
+                if !another {
+                    break;
+                }
+            },
+        }
+
+        let entries = members
+            .into_iter()
+            .map(|(name, member_type)| format!("{name}:{member_type}"))
+            .collect();
+        self.collected
+            .write()
+            .unwrap()
+            .insert("members", GivenAnswer::List(entries));
+
+        Ok(())
+    }
+
+    /// Adds a member to the workspace, running `steps` with the variables of the member
+    /// set: `member_name`, its case-converted forms, `member_type` and `member_dir`. The
+    /// `members` variable lists the names of the members added so far, including this
+    /// one.
+    fn add_member(
+        &mut self,
+        name: &str,
+        member_type: &ProjectType,
+        steps: &[CommandStep],
+        members: &mut Vec<(String, ProjectType)>,
+    ) -> std::result::Result<(), RunnerError> {
+        check_relative_path(Path::new(name)).map_err(|reason| RunnerError::InvalidAnswer {
+            answer: name.to_owned(),
+            reason,
+        })?;
+
+        let member_dir = self
+            .variables
+            .get("project_dir")
+            .map_or_else(|| self.base_dir.clone(), PathBuf::from)
+            .join(name);
+
+        members.push((name.to_owned(), member_type.clone()));
+        if let Some(ref mut journal) = self.journal {
+            journal.creating(&member_dir);
+        }
+
+        self.variables.set(
+            "members",
+            members
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>(),
+        );
+        self.variables.set("member_name", name);
+        self.variables
+            .set("member_name_snake_case", name.to_snake_case());
+        self.variables
+            .set("member_name_kebab_case", name.to_kebab_case());
+        self.variables.set("member_type", member_type.to_string());
+        self.variables
+            .set("member_dir", member_dir.display().to_string());
+
+        self.run_steps(steps)
+    }
+
+    /// Builds the question asked by a prompt step, resolving the placeholders in its
+    /// label, options and default.
+    fn question(&self, step: &CommandStep) -> std::result::Result<Question, RunnerError> {
+        let interpolate_all = |options: &[String]| {
+            options
+                .iter()
+                .map(|option| self.variables.interpolate(option))
+                .collect::<std::result::Result<Vec<_>, _>>()
+        };
+
+        let question = match step.command() {
+            CommandType::PromptText { label, default, .. } => Question::Text {
+                label:      self.variables.interpolate(label)?,
+                default:    default
+                    .as_deref()
+                    .map(|default| self.variables.interpolate(default))
+                    .transpose()?,
+                validation: step.validation().cloned().unwrap_or_default(),
+            },
+            CommandType::PromptChoice {
+                variable,
+                label,
+                options,
+                default,
+            } => {
+                let options = interpolate_all(options)?;
+                let default = match default {
+                    Some(default) => {
+                        let default = self.variables.interpolate(default)?;
+
+                        options
+                            .iter()
+                            .position(|option| *option == default)
+                            .ok_or_else(|| RunnerError::InvalidPrompt {
+                                variable: variable.clone(),
+                                reason:   format!("the default \"{default}\" isn't an option"),
+                            })?
+                    },
+                    None => 0,
+                };
+
+                if options.is_empty() {
+                    return Err(RunnerError::InvalidPrompt {
+                        variable: variable.clone(),
+                        reason:   String::from("there are no options to choose from"),
+                    });
+                }
+
+                Question::Choice {
+                    label: self.variables.interpolate(label)?,
+                    options,
+                    default,
+                }
+            },
+            CommandType::PromptConfirm { label, default, .. } => Question::Confirm {
+                label:   self.variables.interpolate(label)?,
+                default: *default,
+            },
+            CommandType::PromptMultiSelect {
+                variable,
+                label,
+                options,
+                default,
+            } => {
+                let options = interpolate_all(options)?;
+                let default = interpolate_all(default)?
+                    .iter()
+                    .map(|default| {
+                        options
+                            .iter()
+                            .position(|option| option == default)
+                            .ok_or_else(|| RunnerError::InvalidPrompt {
+                                variable: variable.clone(),
+                                reason:   format!("the default \"{default}\" isn't an option"),
+                            })
+                    })
+                    .collect::<std::result::Result<BTreeSet<_>, _>>()?;
+
+                Question::MultiSelect {
+                    label: self.variables.interpolate(label)?,
+                    options,
+                    default,
+                }
+            },
+            _ => unreachable!("only called for prompt steps"),
+        };
+
+        Ok(question)
+    }
+
+    /// The directory that the files written by `step` are relative to, which is the
+    /// project directory unless the step has a `cwd`.
+    fn output_dir(&self, step: &CommandStep) -> std::result::Result<PathBuf, RunnerError> {
+        Ok(match step.cwd() {
+            Some(_) => self.resolve_environment(step)?.working_dir,
+            None => self
+                .variables
+                .get("project_dir")
+                .map_or_else(|| self.base_dir.clone(), PathBuf::from),
+        })
+    }
+
+    /// Resolves the placeholders in each part of `path`. Returns [`None`] if any part
+    /// resolves to nothing, so that files can be left out with a placeholder such as
+    /// `{% if project_type == "Library" %}lib.rs{% endif %}`.
+    fn render_path(&self, path: &Path) -> std::result::Result<Option<PathBuf>, RunnerError> {
+        let mut rendered = PathBuf::new();
+
+        for component in path.iter() {
+            let component = self.variables.interpolate(&component.to_string_lossy())?;
+
+            if component.is_empty() {
+                return Ok(None);
+            }
+
+            rendered.push(component);
+        }
+
+        Ok(Some(rendered))
+    }
+
+    /// Writes `contents` to the file at `path`, creating the directories it's in if
+    /// needed. In dry-run mode, the write is reported instead.
+    fn write_file(
+        &mut self,
+        path: &Path,
+        contents: &[u8],
+        executable: bool,
+    ) -> std::result::Result<(), RunnerError> {
+        if self.dry_run {
+            self.show(RunningConfigMessage::Planned(PlannedAction::WriteFile {
+                path:  path.to_owned(),
+                bytes: contents.len(),
+            }));
+
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            self.create_working_dir(parent)?;
+        }
+
+        if let Some(ref mut journal) = self.journal {
+            journal.creating(path);
+        }
+
+        fs::write(path, contents)
+            .and_then(|()| {
+                if executable {
+                    make_executable(path)
+                } else {
+                    Ok(())
+                }
+            })
+            .map_err(|error| RunnerError::CouldNotWriteFile {
+                path:   path.to_owned(),
+                reason: error.to_string(),
+            })
+    }
+
+    /// Where the files that `step` uses are read from: the plugin it was inherited from,
+    /// or otherwise the plugin being run.
+    fn plugin_source<'a>(&'a self, step: &'a CommandStep) -> Option<&'a PluginSource> {
+        step.source().or_else(|| self.config.source())
+    }
+
+    /// Gets the contents of `template`, along with a name to refer to it by in errors.
+    /// Template files are read from the directory that the plugin of `step` was loaded
+    /// from.
+    fn read_template(
+        &self,
+        step: &CommandStep,
+        template: &Template,
+    ) -> std::result::Result<(String, String), RunnerError> {
+        match template {
+            Template::Inline(contents) => Ok((String::from("<inline>"), contents.clone())),
+            Template::File(file) => {
+                let could_not_read = |reason: String| RunnerError::CouldNotReadTemplate {
+                    path: PathBuf::from(file),
+                    reason,
+                };
+
+                let contents = match self.plugin_source(step) {
+                    Some(source) => source
+                        .read(Path::new(file))
+                        .map_err(|error| could_not_read(error.to_string()))?,
+                    None => return Err(could_not_read(String::from("the plugin has no files"))),
+                };
+
+                String::from_utf8(contents)
+                    .map(|contents| (file.clone(), contents))
+                    .map_err(|error| could_not_read(error.to_string()))
+            },
+        }
+    }
+}
+
+/// Parses a member of a workspace given before the run, as `name` or `name:type`. The
+/// type is one of `types`, ignoring case, and is the first of them if it isn't given.
+fn parse_member(
+    entry: &str,
+    types: &[ProjectType],
+    validation: &Validation,
+) -> std::result::Result<(String, ProjectType), RunnerError> {
+    let invalid = |reason: String| RunnerError::InvalidAnswer {
+        answer: entry.to_owned(),
+        reason,
+    };
+
+    let (name, member_type) = match entry.split_once(':') {
+        Some((name, member_type)) => {
+            let member_type = types
+                .iter()
+                .find(|project_type| {
+                    project_type
+                        .to_string()
+                        .eq_ignore_ascii_case(member_type.trim())
+                })
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "the member types are {}",
+                        types
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+
+            (name.trim(), member_type.clone())
+        },
+        None => (entry.trim(), types[0].clone()),
+    };
+
+    validation.check(name).map_err(invalid)?;
+
+    Ok((name.to_owned(), member_type))
+}
+
+/// Checks that `path` stays inside the directory it's relative to, so that an answer or a
+/// plugin can't make the run write anywhere else: it has to name something, and can't be
+/// absolute or go up with `..`.
+fn check_relative_path(path: &Path) -> std::result::Result<(), String> {
+    if path.has_root()
+        || path
+            .components()
+            .any(|component| matches!(component, Component::Prefix(_) | Component::ParentDir))
+    {
+        return Err(String::from(
+            "it has to be a path inside the directory, without any ..",
+        ));
+    }
+
+    if !path
+        .components()
+        .any(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(String::from("it can't be empty"));
+    }
+
+    Ok(())
+}
+
+/// Splits a string of arguments on whitespace, keeping anything wrapped in single or
+/// double quotes together as a single argument. Placeholders (`{{ ... }}` and `{% ...
+/// %}`) are never split, so that they can be resolved after splitting.
+pub(super) fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_argument = false;
+    let mut in_placeholder = false;
+    let mut previous = None;
+
+    for character in arguments.chars() {
+        match (quote, character) {
+            _ if in_placeholder => {
+                in_placeholder = !matches!((previous, character), (Some('}' | '%'), '}'));
+                current.push(character);
+            },
+            (None, '{' | '%') if previous == Some('{') => {
+                in_placeholder = true;
+                current.push(character);
+            },
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(character);
+                in_argument = true;
+            },
+            (None, c) if c.is_whitespace() =>
+                if in_argument {
+                    split.push(std::mem::take(&mut current));
+                    in_argument = false;
+                },
+            (None, c) => {
+                current.push(c);
+                in_argument = true;
+            },
+        }
+
+        previous = Some(character);
+    }
+
+    if in_argument {
+        split.push(current);
+    }
+
+    split
+}
+
+/// Resolves the placeholders in the command and arguments of a [`CommandType::Command`]
+/// against `variables`, once the arguments have been split. Arguments that consist of
+/// placeholders which resolve to nothing are dropped, so that `{% if ... %}--flag{% endif
+/// %}` can be used for optional flags.
+fn resolve_command(
+    command: &str,
+    arguments: &str,
+    variables: &Variables,
+) -> std::result::Result<(String, Vec<String>), RunnerError> {
+    let command = variables.interpolate(command)?;
+    let mut resolved = Vec::new();
+
+    for argument in split_arguments(arguments) {
+        let value = variables.interpolate(&argument)?;
+
+        if value.is_empty() && (argument.contains("{{") || argument.contains("{%")) {
+            continue;
+        }
+
+        resolved.push(value);
+    }
+
+    Ok((command, resolved))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, process};
+
+    use super::*;
+    use crate::config::{parser::load_plugin_file, PluginScope};
+
+    /// A frontend that doesn't answer any questions, other than the choices it's given,
+    /// keeping the messages it's shown.
+    #[derive(Default)]
+    struct Recorder {
+        /// The options chosen by [`Frontend::ask_choice`], in order. Choices asked for
+        /// after these run out aren't answered.
+        choices:  VecDeque<usize>,
+        messages: Vec<RunningConfigMessage>,
+    }
+
+    impl Frontend for Recorder {
+        fn ask_text(&mut self, _: &str, _: Option<&str>, _: &Validation) -> Option<String> { None }
+
+        fn ask_choice(&mut self, _: &str, _: &[String], _: usize) -> Option<usize> {
+            self.choices.pop_front()
+        }
+
+        fn ask_multi_select(
+            &mut self,
+            _: &str,
+            _: &[String],
+            _: &BTreeSet<usize>,
+        ) -> Option<BTreeSet<usize>> {
+            None
+        }
+
+        fn show(&mut self, message: RunningConfigMessage) { self.messages.push(message); }
+    }
+
+    /// An empty directory of its own in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("proman-runner-{}-{name}", process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Loads `source` as a plugin bundle in `dir`, so that it can use the files put next
+    /// to it.
+    fn plugin(dir: &Path, source: &str) -> LanguageConfig {
+        let path = dir.join("plugin.ron");
+        fs::write(&path, source).unwrap();
+
+        load_plugin_file(&path, PluginScope::User).unwrap()
+    }
+
+    /// The text `answers` to give to a run, by the variables they answer.
+    fn text_answers(answers: &[(&str, &str)]) -> Answers {
+        let mut given = Answers::new();
+        for (variable, answer) in answers {
+            given.insert(*variable, GivenAnswer::Text(answer.to_string()));
+        }
+        given
+    }
+
+    /// Runs `config` in `dir` without asking anything, with the text `answers` given.
+    fn run(
+        config: &LanguageConfig,
+        dir: &Path,
+        answers: &[(&str, &str)],
+        frontend: &mut Recorder,
+    ) -> std::result::Result<(), RunnerError> {
+        config
+            .create_runner()
+            .with_answers(text_answers(answers))
+            .with_interactive(false)
+            .with_base_dir(dir)
+            .run(frontend)
+    }
+
+    /// A plugin that asks for the project's name and then copies its template.
+    const TEMPLATE_PLUGIN: &str = r#"(
+        language: "Template",
+        command_steps: [
+            Step(name: "Name", id: "project_name", command: PromptProjectName),
+            Step(name: "Copy", command: CopyTemplate),
+        ],
+    )"#;
+
+    /// Writes `contents` to `path` in the template directory of the bundle in `dir`.
+    fn template_file(dir: &Path, path: &str, contents: &[u8]) -> PathBuf {
+        let path = dir.join(TEMPLATE_DIR).join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// The actions planned by a dry run, as they're shown.
+    fn planned(frontend: &Recorder) -> Vec<String> {
+        frontend
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                RunningConfigMessage::Planned(action) => Some(action.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The lines of output shown while running commands.
+    fn output(frontend: &Recorder) -> Vec<&str> {
+        frontend
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                RunningConfigMessage::CommandOutput(line) => Some(line.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_shell_commands_and_shows_their_output() {
+        let dir = temp_dir("shell-command");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Shell",
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(
+                        name: "Echo",
+                        command: ShellCommand("sh", "-c 'echo made {{ project_name }}; echo on stderr >&2; touch made'"),
+                    ),
+                ],
+            )"#,
+        );
+        let mut frontend = Recorder::default();
+
+        run(&config, &dir, &[("project_name", "demo")], &mut frontend).unwrap();
+
+        let mut lines = output(&frontend);
+        lines.sort_unstable();
+        assert_eq!(lines, ["made demo", "on stderr"]);
+        assert!(frontend.messages.iter().any(|message| matches!(
+            message,
+            RunningConfigMessage::CommandExited(status) if status.success()
+        )));
+        assert!(dir.join("made").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fails_when_a_shell_command_fails() {
+        let dir = temp_dir("shell-command-fails");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Shell",
+                command_steps: [
+                    Step(name: "Fail", command: ShellCommand("sh", "-c 'exit 3'")),
+                    Step(name: "Never", command: ShellCommand("touch", "never")),
+                ],
+            )"#,
+        );
+
+        let result = run(&config, &dir, &[], &mut Recorder::default());
+
+        match result {
+            Err(RunnerError::CommandFailed { command, status }) => {
+                assert_eq!(command, "sh");
+                assert_eq!(status.code(), Some(3));
+            },
+            result => panic!("{result:?}"),
+        }
+        assert!(!dir.join("never").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn skips_steps_whose_conditions_do_not_hold() {
+        let dir = temp_dir("when");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "When",
+                project_types: [Binary, Library],
+                command_steps: [
+                    Step(name: "Type", id: "project_type", command: PromptProjectType),
+                    Step(
+                        name: "Binary",
+                        command: WriteFile(path: "main.rs", template: Inline("")),
+                        when: ProjectType(Binary),
+                    ),
+                    Step(
+                        name: "Library",
+                        command: WriteFile(path: "lib.rs", template: Inline("")),
+                        when: ProjectType(Library),
+                    ),
+                    Step(
+                        name: "Not binary",
+                        command: WriteFile(path: "not-binary.txt", template: Inline("")),
+                        when: Not(ProjectType(Binary)),
+                    ),
+                ],
+            )"#,
+        );
+        let mut frontend = Recorder::default();
+
+        run(&config, &dir, &[("project_type", "library")], &mut frontend).unwrap();
+
+        assert!(!dir.join("main.rs").exists());
+        assert!(dir.join("lib.rs").exists());
+        assert!(dir.join("not-binary.txt").exists());
+        let skipped = frontend
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                RunningConfigMessage::StepSkipped(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, ["Binary"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_steps_in_their_directory_with_their_environment() {
+        let dir = temp_dir("cwd-env");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Env",
+                env: {"SHARED": "plugin", "OVERRIDDEN": "plugin"},
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(
+                        name: "Step",
+                        command: ShellCommand("sh", "-c 'echo $SHARED $OVERRIDDEN; touch here'"),
+                        cwd: "{{ project_name }}/sub",
+                        env: {"OVERRIDDEN": "step {{ project_name }}"},
+                    ),
+                    Step(
+                        name: "Plugin",
+                        command: ShellCommand("sh", "-c 'echo $OVERRIDDEN; touch here'"),
+                    ),
+                ],
+            )"#,
+        );
+        let mut frontend = Recorder::default();
+
+        run(&config, &dir, &[("project_name", "demo")], &mut frontend).unwrap();
+
+        assert_eq!(output(&frontend), ["plugin step demo", "plugin"]);
+        assert!(dir.join("demo/sub/here").exists());
+        assert!(dir.join("here").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn plans_the_directory_and_environment_of_steps() {
+        let dir = temp_dir("cwd-env-plan");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Env",
+                env: {"SHARED": "plugin"},
+                command_steps: [
+                    Step(
+                        name: "Step",
+                        command: ShellCommand("true", ""),
+                        cwd: "sub",
+                        env: {"STEP": "{{ year }}"},
+                    ),
+                ],
+            )"#,
+        );
+        let mut frontend = Recorder::default();
+
+        config
+            .create_runner()
+            .with_interactive(false)
+            .with_dry_run(true)
+            .with_base_dir(&dir)
+            .run(&mut frontend)
+            .unwrap();
+
+        let environment = frontend
+            .messages
+            .iter()
+            .find_map(|message| match message {
+                RunningConfigMessage::Planned(PlannedAction::RunCommand {
+                    environment, ..
+                }) => Some(environment.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(environment.working_dir, dir.join("sub"));
+        assert_eq!(environment.env["SHARED"], "plugin");
+        assert_eq!(
+            environment.env["STEP"],
+            chrono::Local::now().format("%Y").to_string()
+        );
+        // a dry run doesn't create the directory.
+        assert!(!dir.join("sub").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// A plugin whose first step fails until it has been run `succeeds_on` times, with
+    /// the given failure policy, counting its runs in `attempts`. The second step writes
+    /// `after`.
+    fn failing_plugin(dir: &Path, on_failure: &str, succeeds_on: usize) -> LanguageConfig {
+        plugin(
+            dir,
+            &format!(
+                r#"(
+                    language: "Failing",
+                    command_steps: [
+                        Step(
+                            name: "Flaky",
+                            command: ShellCommand("sh", "-c 'echo >> attempts; test $(wc -l < attempts) -ge {succeeds_on}'"),
+                            on_failure: {on_failure},
+                        ),
+                        Step(name: "After", command: WriteFile(path: "after", template: Inline(""))),
+                    ],
+                )"#
+            ),
+        )
+    }
+
+    /// How many times the step of [`failing_plugin`] was run.
+    fn attempts(dir: &Path) -> usize {
+        fs::read_to_string(dir.join("attempts"))
+            .unwrap()
+            .lines()
+            .count()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn aborts_when_a_step_fails() {
+        let dir = temp_dir("abort");
+        let config = failing_plugin(&dir, "Abort", 2);
+
+        let result = run(&config, &dir, &[], &mut Recorder::default());
+
+        assert!(matches!(result, Err(RunnerError::CommandFailed { .. })));
+        assert_eq!(attempts(&dir), 1);
+        assert!(!dir.join("after").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn retries_a_step_that_fails() {
+        let dir = temp_dir("retry");
+        let config = failing_plugin(&dir, "Retry(2)", 3);
+
+        run(&config, &dir, &[], &mut Recorder::default()).unwrap();
+
+        assert_eq!(attempts(&dir), 3);
+        assert!(dir.join("after").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn aborts_once_the_retries_run_out() {
+        let dir = temp_dir("retries-run-out");
+        let config = failing_plugin(&dir, "Retry(2)", 4);
+
+        let result = run(&config, &dir, &[], &mut Recorder::default());
+
+        assert!(matches!(result, Err(RunnerError::CommandFailed { .. })));
+        assert_eq!(attempts(&dir), 3);
+        assert!(!dir.join("after").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_a_step_that_fails() {
+        let dir = temp_dir("skip");
+        let config = failing_plugin(&dir, "Skip", 2);
+        let mut frontend = Recorder::default();
+
+        run(&config, &dir, &[], &mut frontend).unwrap();
+
+        assert_eq!(attempts(&dir), 1);
+        assert!(dir.join("after").exists());
+        assert!(frontend
+            .messages
+            .iter()
+            .any(|message| matches!(message, RunningConfigMessage::StepFailed(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn asks_what_to_do_when_a_step_fails() {
+        let dir = temp_dir("ask");
+        let config = failing_plugin(&dir, "Ask", 3);
+        // retry once, then skip.
+        let mut frontend = Recorder {
+            choices: VecDeque::from([0, 1]),
+            ..Recorder::default()
+        };
+
+        run(&config, &dir, &[], &mut frontend).unwrap();
+
+        assert_eq!(attempts(&dir), 2);
+        assert!(dir.join("after").exists());
+
+        // not answering aborts the run.
+        fs::remove_file(dir.join("attempts")).unwrap();
+        fs::remove_file(dir.join("after")).unwrap();
+        let result = run(&config, &dir, &[], &mut Recorder::default());
+
+        assert!(matches!(result, Err(RunnerError::CommandFailed { .. })));
+        assert_eq!(attempts(&dir), 1);
+        assert!(!dir.join("after").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn plans_a_dry_run_without_changing_anything() {
+        let dir = temp_dir("dry-run");
+        let config = plugin(
+            &dir,
+            r##"(
+                language: "Dry",
+                requirements: ["proman-missing-tool"],
+                tools: {"proman-missing-tool": (install_hint: "Install it.")},
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(
+                        name: "Create",
+                        command: ShellCommand("proman-missing-tool", "new {{ project_name }}"),
+                    ),
+                    Step(
+                        name: "Write",
+                        command: WriteFile(path: "README.md", template: Inline("# {{ project_name }}")),
+                    ),
+                ],
+            )"##,
+        );
+        let mut frontend = Recorder::default();
+
+        config
+            .create_runner()
+            .with_answers(text_answers(&[("project_name", "demo")]))
+            .with_interactive(false)
+            .with_dry_run(true)
+            .with_base_dir(&dir)
+            .run(&mut frontend)
+            .unwrap();
+
+        let planned = planned(&frontend);
+        assert_eq!(planned.len(), 3);
+        assert!(planned[0].starts_with("warning: proman-missing-tool: not found on PATH"));
+        assert!(planned[1].starts_with("run `proman-missing-tool new demo`"));
+        assert_eq!(
+            planned[2],
+            format!("write {} (6 bytes)", dir.join("demo/README.md").display())
+        );
+        assert!(!dir.join("demo").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parses_workspace_members() {
+        let types = [ProjectType::Binary, ProjectType::Library];
+        let validation = Validation::default();
+
+        assert_eq!(
+            parse_member(" api ", &types, &validation).unwrap(),
+            (String::from("api"), ProjectType::Binary)
+        );
+        assert_eq!(
+            parse_member("core : LIBRARY", &types, &validation).unwrap(),
+            (String::from("core"), ProjectType::Library)
+        );
+        assert!(matches!(
+            parse_member("core:workspace", &types, &validation),
+            Err(RunnerError::InvalidAnswer { reason, .. }) if reason == "the member types are Binary, Library"
+        ));
+    }
+
+    #[test]
+    fn checks_the_names_of_workspace_members() {
+        let validation = ron::from_str::<Validation>(r#"(forbidden: ["std"])"#).unwrap();
+
+        assert!(matches!(
+            parse_member("std:library", &[ProjectType::Library], &validation),
+            Err(RunnerError::InvalidAnswer { answer, .. }) if answer == "std:library"
+        ));
+    }
+
+    #[test]
+    fn creates_the_members_of_a_workspace() {
+        let dir = temp_dir("workspace");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Workspace",
+                project_types: [Workspace, Binary, Library],
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(name: "Type", id: "project_type", command: PromptProjectType),
+                    Step(name: "Members", command: CreateMembers, when: ProjectType(Workspace)),
+                    Step(
+                        name: "Manifest",
+                        command: WriteFile(path: "members", template: Inline("{% for member in members %}{{ member }} {% endfor %}")),
+                    ),
+                ],
+                member_steps: [
+                    Step(
+                        name: "Member",
+                        command: WriteFile(path: "{{ member_name }}/type", template: Inline("{{ member_type }}")),
+                    ),
+                ],
+            )"#,
+        );
+
+        run(
+            &config,
+            &dir,
+            &[
+                ("project_name", "demo"),
+                ("project_type", "Workspace"),
+                ("members", "api, core:library"),
+            ],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        let project = dir.join("demo");
+        assert_eq!(
+            fs::read_to_string(project.join("api/type")).unwrap(),
+            "Binary"
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("core/type")).unwrap(),
+            "Library"
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("members")).unwrap(),
+            "api core "
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_allows_paths_inside_the_directory() {
+        for path in ["demo", "demo/src", "./demo", "a/./b"] {
+            assert_eq!(check_relative_path(Path::new(path)), Ok(()), "{path}");
+        }
+        for path in [
+            "",
+            ".",
+            "/tmp/demo",
+            "..",
+            "../demo",
+            "demo/../../x",
+            "a/..",
+        ] {
+            assert!(check_relative_path(Path::new(path)).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn rejects_project_names_outside_the_base_dir() {
+        let dir = temp_dir("project-names");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Names",
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(name: "Write", command: WriteFile(path: "x", template: Inline(""))),
+                ],
+            )"#,
+        );
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+
+        for name in ["", ".", "..", "../escaped", "/tmp/escaped", "demo/../.."] {
+            let result = run(
+                &config,
+                &out,
+                &[("project_name", name)],
+                &mut Recorder::default(),
+            );
+
+            assert!(
+                matches!(result, Err(RunnerError::InvalidAnswer { .. })),
+                "{name}: {result:?}"
+            );
+        }
+        assert_eq!(fs::read_dir(&out).unwrap().count(), 0);
+        assert!(!dir.join("escaped").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_write_files_outside_the_project() {
+        let dir = temp_dir("write-outside");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Escape",
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(name: "Write", command: WriteFile(path: "../{{ project_name }}.txt", template: Inline(""))),
+                ],
+            )"#,
+        );
+
+        let result = run(
+            &config,
+            &dir,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        );
+
+        assert!(
+            matches!(result, Err(RunnerError::CouldNotWriteFile { .. })),
+            "{result:?}"
+        );
+        assert!(!dir.join("demo.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn writes_files_from_templates() {
+        let dir = temp_dir("write-file");
+        let bundle = dir.join("bundle");
+        fs::create_dir_all(bundle.join("templates")).unwrap();
+        fs::write(
+            bundle.join("templates/README.md"),
+            "# {{ project_name }}\n{% if project_name == 'demo' %}\nA demo.\n{% endif %}\n",
+        )
+        .unwrap();
+        let config = plugin(
+            &bundle,
+            r#"(
+                language: "Write",
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                    Step(
+                        name: "README",
+                        command: WriteFile(path: "README.md", template: File("templates/README.md")),
+                    ),
+                    Step(
+                        name: "Inline",
+                        command: WriteFile(path: "src/{{ project_name }}.txt", template: Inline("{{ project_name | upper }}")),
+                    ),
+                    Step(
+                        name: "Elsewhere",
+                        command: WriteFile(path: "notes.txt", template: Inline("notes")),
+                        cwd: "docs",
+                    ),
+                ],
+            )"#,
+        );
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+
+        run(
+            &config,
+            &out,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(out.join("demo/README.md")).unwrap(),
+            "# demo\nA demo.\n"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("demo/src/demo.txt")).unwrap(),
+            "DEMO"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("docs/notes.txt")).unwrap(),
+            "notes"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fails_to_write_files_from_bad_templates() {
+        let dir = temp_dir("write-file-fails");
+        let write = |template: &str| {
+            plugin(
+                &dir,
+                &format!(
+                    r#"(
+                        language: "Write",
+                        command_steps: [
+                            Step(name: "Write", command: WriteFile(path: "out.txt", template: {template})),
+                        ],
+                    )"#
+                ),
+            )
+        };
+
+        let result = run(
+            &write(r#"File("missing.txt")"#),
+            &dir,
+            &[],
+            &mut Recorder::default(),
+        );
+        assert!(
+            matches!(result, Err(RunnerError::CouldNotReadTemplate { .. })),
+            "{result:?}"
+        );
+
+        let result = run(
+            &write(r#"Inline("{{ undefined }}")"#),
+            &dir,
+            &[],
+            &mut Recorder::default(),
+        );
+        assert!(
+            matches!(result, Err(RunnerError::CouldNotRenderTemplate { .. })),
+            "{result:?}"
+        );
+        assert!(!dir.join("out.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fails_when_the_plugin_environment_uses_unknown_variables() {
+        let dir = temp_dir("plugin-env");
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Env",
+                env: {"PROJECT": "{{ project_name }}"},
+                command_steps: [
+                    Step(name: "Name", id: "project_name", command: PromptProjectName),
+                ],
+            )"#,
+        );
+
+        let result = run(
+            &config,
+            &dir,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        );
+
+        assert!(
+            matches!(result, Err(RunnerError::CouldNotInterpolate { .. })),
+            "{result:?}"
+        );
+        assert!(!dir.join("demo").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_required_tools_on_the_plugin_path() {
+        let dir = temp_dir("plugin-path");
+        // the directory is named with a placeholder, which is resolved before the
+        // requirements are checked.
+        let user = Variables::with_builtins().get("user").unwrap().to_owned();
+        let bin = dir.join(format!("{user}-bin"));
+        let tool = bin.join("proman-test-tool");
+        fs::create_dir(&bin).unwrap();
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        make_executable(&tool).unwrap();
+        let config = plugin(
+            &dir,
+            r#"(
+                language: "Path",
+                requirements: ["proman-test-tool"],
+                path: ["{{ user }}-bin"],
+                command_steps: [],
+            )"#,
+        );
+
+        run(&config, &dir, &[], &mut Recorder::default()).unwrap();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn renders_the_names_of_template_files() {
+        let dir = temp_dir("template-names");
+        let bundle = dir.join("bundle");
+        template_file(
+            &bundle,
+            "{{ project_name }}/{{ project_name }}.txt",
+            b"name",
+        );
+        template_file(&bundle, "{% if false %}skipped.txt{% endif %}", b"left out");
+        let config = plugin(&bundle, TEMPLATE_PLUGIN);
+
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+        run(
+            &config,
+            &out,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        let project = out.join("demo");
+        assert_eq!(fs::read(project.join("demo/demo.txt")).unwrap(), b"name");
+        assert_eq!(fs::read_dir(&project).unwrap().count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn renders_only_the_template_files_that_ask_to_be() {
+        let dir = temp_dir("template-contents");
+        let bundle = dir.join("bundle");
+        template_file(&bundle, "README.md.jinja", b"# {{ project_name }}\n");
+        template_file(&bundle, "ci.yml", b"token: ${{ secrets.TOKEN }}\n");
+        let config = plugin(&bundle, TEMPLATE_PLUGIN);
+
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+        run(
+            &config,
+            &out,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        let project = out.join("demo");
+        assert_eq!(
+            fs::read_to_string(project.join("README.md")).unwrap(),
+            "# demo\n"
+        );
+        assert!(!project.join("README.md.jinja").exists());
+        assert_eq!(
+            fs::read_to_string(project.join("ci.yml")).unwrap(),
+            "token: ${{ secrets.TOKEN }}\n"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copies_binary_template_files_unchanged() {
+        let dir = temp_dir("template-binary");
+        let bundle = dir.join("bundle");
+        let bytes = [0x89, b'P', b'N', b'G', 0x00, 0xff, b'{', b'{', 0xfe];
+        template_file(&bundle, "logo.png", &bytes);
+        let config = plugin(&bundle, TEMPLATE_PLUGIN);
+
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+        run(
+            &config,
+            &out,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(out.join("demo/logo.png")).unwrap(), bytes);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_template_files_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("template-executable");
+        let bundle = dir.join("bundle");
+        let script = template_file(&bundle, "build-{{ project_name }}.sh.jinja", b"echo hi\n");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        template_file(&bundle, "notes.txt", b"notes");
+        let config = plugin(&bundle, TEMPLATE_PLUGIN);
+
+        let out = dir.join("out");
+        fs::create_dir(&out).unwrap();
+        run(
+            &config,
+            &out,
+            &[("project_name", "demo")],
+            &mut Recorder::default(),
+        )
+        .unwrap();
+
+        let mode = |path: &str| {
+            fs::metadata(out.join("demo").join(path))
+                .unwrap()
+                .permissions()
+                .mode()
+        };
+        assert_ne!(mode("build-demo.sh") & 0o111, 0);
+        assert_eq!(mode("notes.txt") & 0o111, 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn splits_arguments_on_whitespace() {
+        assert_eq!(split_arguments("  new   --lib "), ["new", "--lib"]);
+        assert!(split_arguments("").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_arguments_together() {
+        assert_eq!(
+            split_arguments(r#"commit -m "first commit" --author='A B'"#),
+            ["commit", "-m", "first commit", "--author=A B"]
+        );
+        assert_eq!(split_arguments(r#"echo "" 'it"s'"#), ["echo", "", "it\"s"]);
+    }
+
+    #[test]
+    fn keeps_placeholders_together() {
+        assert_eq!(
+            split_arguments("new {{ project_name | lower }} {% if lib %}--lib{% endif %}"),
+            [
+                "new",
+                "{{ project_name | lower }}",
+                "{% if lib %}--lib{% endif %}"
+            ]
+        );
+    }
+}
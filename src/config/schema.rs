@@ -0,0 +1,295 @@
+use std::{fs, path::Path};
+
+use super::{parse_language_config, LanguageConfig, PluginDiagnostic, Version};
+
+/// The version of the plugin format that this version of `proman` reads and writes.
+/// Plugins that don't give a `schema_version` are written for version 1.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A change to the plugin format, made to a plugin once it has been parsed.
+type MigrationStep = fn(&mut LanguageConfig);
+
+/// The changes to the plugin format. The migration at index `n` brings a plugin from
+/// version `n + 1` to version `n + 2`. Migrations are made to the parsed plugin rather
+/// than its source, so a field whose type changes has to keep accepting its old form
+/// until the plugin is migrated.
+const MIGRATIONS: [MigrationStep; SCHEMA_VERSION as usize - 1] = [];
+
+/// What migrating a plugin file did to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Migration {
+    /// The plugin was already written for [`SCHEMA_VERSION`].
+    UpToDate,
+    /// The plugin was rewritten from the schema version it was written for.
+    Migrated(u32),
+}
+
+/// The fields of a plugin that say which versions of `proman` can load it. The rest of
+/// the plugin is ignored, so that these can be read before it is migrated.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub(crate) struct PluginHeader {
+    #[serde(default)]
+    schema_version:     Option<u32>,
+    #[serde(default)]
+    min_proman_version: Option<String>,
+}
+
+impl PluginHeader {
+    /// Reads the header of the plugin in `source`. A plugin that can't be parsed is taken
+    /// to be for version 1, so that the problem is reported once the whole plugin is
+    /// parsed.
+    pub(crate) fn read(source: &str) -> Self {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn schema_version(&self) -> u32 { self.schema_version.unwrap_or(1) }
+
+    /// Fails if the plugin at `path` is written for a schema that is newer than this
+    /// version of `proman` knows about.
+    pub(crate) fn check_schema(&self, path: &Path) -> Result<(), PluginDiagnostic> {
+        if self.schema_version() <= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        Err(PluginDiagnostic::new(
+            path,
+            format!(
+                "the plugin is written for version {} of the plugin schema, but this version of \
+                 proman only knows up to version {SCHEMA_VERSION}",
+                self.schema_version()
+            ),
+        )
+        .with_suggestion(Some(String::from("update proman to load this plugin"))))
+    }
+
+    /// Fails if the plugin at `path` needs a newer version of `proman` than this one.
+    pub(crate) fn check_proman_version(&self, path: &Path) -> Result<(), PluginDiagnostic> {
+        let Some(ref min_version) = self.min_proman_version else {
+            return Ok(());
+        };
+
+        let min_version = min_version.parse::<Version>().map_err(|error| {
+            PluginDiagnostic::new(path, format!("`min_proman_version` is invalid: {error}"))
+        })?;
+        let version = env!("CARGO_PKG_VERSION")
+            .parse::<Version>()
+            .expect("the version of proman should be a version number");
+
+        if version >= min_version {
+            return Ok(());
+        }
+
+        Err(PluginDiagnostic::new(
+            path,
+            format!("the plugin needs proman {min_version} or newer, but this is proman {version}"),
+        )
+        .with_suggestion(Some(String::from("update proman to load this plugin"))))
+    }
+}
+
+/// Brings `config` up to [`SCHEMA_VERSION`], returning the schema version it was written
+/// for if it had to be migrated.
+pub(crate) fn migrate(config: &mut LanguageConfig) -> Option<u32> {
+    migrate_with(config, &MIGRATIONS)
+}
+
+/// Makes the `migrations` to `config` that it is missing, as described by [`MIGRATIONS`].
+fn migrate_with(config: &mut LanguageConfig, migrations: &[MigrationStep]) -> Option<u32> {
+    let from = config.schema_version();
+    let pending = usize::try_from(from.max(1))
+        .ok()
+        .and_then(|from| migrations.get(from - 1..))
+        .filter(|pending| !pending.is_empty())?;
+
+    for migration in pending {
+        migration(config);
+    }
+
+    config.set_schema_version(u32::try_from(migrations.len()).unwrap_or(u32::MAX) + 1);
+
+    Some(from)
+}
+
+/// Rewrites the plugin file at `path` in place for [`SCHEMA_VERSION`]. The plugin is
+/// parsed, migrated and written out again, so its comments and layout aren't kept. The
+/// migrated plugin is parsed before it is written, so that a plugin is never replaced
+/// with one that can't be loaded. With `dry_run`, the file is left as it is.
+pub fn migrate_plugin_file(path: &Path, dry_run: bool) -> Result<Migration, PluginDiagnostic> {
+    let source =
+        fs::read_to_string(path).map_err(|error| PluginDiagnostic::from_io(path, &error))?;
+
+    PluginHeader::read(&source).check_schema(path)?;
+
+    let mut config =
+        parse_language_config(&source).map_err(|error| PluginDiagnostic::from_ron(path, &error))?;
+    let Some(from) = migrate(&mut config) else {
+        return Ok(Migration::UpToDate);
+    };
+
+    let migrated = plugin_source(&config).map_err(|error| {
+        PluginDiagnostic::new(
+            path,
+            format!("could not write the migrated plugin: {error}"),
+        )
+    })?;
+    parse_language_config(&migrated).map_err(|error| PluginDiagnostic::from_ron(path, &error))?;
+
+    if !dry_run {
+        fs::write(path, migrated).map_err(|error| {
+            PluginDiagnostic::new(
+                path,
+                format!("could not write the migrated plugin: {error}"),
+            )
+        })?;
+    }
+
+    Ok(Migration::Migrated(from))
+}
+
+/// Writes `config` as the source of a plugin file, in the same form that plugins are
+/// read in.
+fn plugin_source(config: &LanguageConfig) -> ron::Result<String> {
+    let pretty = ron::ser::PrettyConfig::default().struct_names(true);
+
+    ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+        .to_string_pretty(config, pretty)
+        .map(|source| source + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, process};
+
+    use super::*;
+
+    const PLUGIN: &str = r#"// a plugin written for version 1
+(
+    language: "Go",
+    requirements: ["go >= 1.21"],
+    tools: {
+        "go": (version_command: ("go", "version")),
+    },
+    command_steps: [
+        Step(name: "name", command: PromptProjectName),
+        Step(
+            name: "init",
+            id: "init",
+            command: ShellCommand("go", "mod init {{ project_name }}"),
+            when: Not(ProjectType(Workspace)),
+            cwd: "{{ project_name }}",
+            timeout: 60,
+        ),
+    ],
+)
+"#;
+
+    /// Writes `source` to a file of its own in the temporary directory.
+    fn plugin_file(name: &str, source: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("proman-{}-{name}.ron", process::id()));
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn header(source: &str) -> PluginHeader { PluginHeader::read(source) }
+
+    #[test]
+    fn reads_the_schema_version() {
+        assert_eq!(header(PLUGIN).schema_version(), 1);
+        assert_eq!(
+            header(r#"(schema_version: 3, language: "Go")"#).schema_version(),
+            3
+        );
+        // a plugin that can't be parsed is reported once it's parsed in full.
+        assert_eq!(header("(language: ").schema_version(), 1);
+    }
+
+    #[test]
+    fn rejects_newer_schemas() {
+        let path = Path::new("go.ron");
+
+        assert!(header(PLUGIN).check_schema(path).is_ok());
+
+        let newer = format!("(schema_version: {}, language: \"Go\")", SCHEMA_VERSION + 1);
+        let error = header(&newer).check_schema(path).unwrap_err();
+
+        assert_eq!(
+            error.suggestion(),
+            Some("update proman to load this plugin")
+        );
+    }
+
+    #[test]
+    fn checks_the_proman_version() {
+        let path = Path::new("go.ron");
+
+        assert!(header(r#"(min_proman_version: "0.1", language: "Go")"#)
+            .check_proman_version(path)
+            .is_ok());
+        assert!(header(r#"(min_proman_version: "999.0", language: "Go")"#)
+            .check_proman_version(path)
+            .is_err());
+        assert!(header(r#"(min_proman_version: "soon", language: "Go")"#)
+            .check_proman_version(path)
+            .unwrap_err()
+            .message()
+            .contains("`min_proman_version` is invalid"));
+    }
+
+    #[test]
+    fn makes_the_migrations_a_plugin_is_missing() {
+        fn add_git(config: &mut LanguageConfig) {
+            let mut plugin = plugin_source(config).unwrap();
+            plugin = plugin.replacen("requirements: [", "requirements: [\"git\", ", 1);
+            *config = parse_language_config(&plugin).unwrap();
+        }
+        fn already_made(_: &mut LanguageConfig) { panic!("the plugin is already past this") }
+
+        let mut config = parse_language_config(PLUGIN).unwrap();
+
+        assert_eq!(migrate_with(&mut config, &[add_git]), Some(1));
+        assert_eq!(config.schema_version(), 2);
+        assert_eq!(config.requirements()[0].tool(), "git");
+
+        assert_eq!(migrate_with(&mut config, &[already_made]), None);
+        assert_eq!(migrate_with(&mut config, &[already_made, add_git]), Some(2));
+        assert_eq!(config.schema_version(), 3);
+    }
+
+    #[test]
+    fn writes_plugins_that_parse_the_same() {
+        let mut config = parse_language_config(PLUGIN).unwrap();
+        config.set_schema_version(SCHEMA_VERSION);
+
+        let source = plugin_source(&config).unwrap();
+
+        assert_eq!(parse_language_config(&source).unwrap(), config);
+    }
+
+    #[test]
+    fn leaves_up_to_date_plugins_alone() {
+        let path = plugin_file("up-to-date", PLUGIN);
+
+        assert_eq!(migrate_plugin_file(&path, false), Ok(Migration::UpToDate));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(contents, PLUGIN);
+    }
+
+    #[test]
+    fn reports_plugins_that_cannot_be_migrated() {
+        let path = plugin_file("broken", "(language: \"Go\", unknown: 1)");
+        let error = migrate_plugin_file(&path, false).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert!(
+            error.message().contains("unknown field `unknown`"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use regex::Regex;
+
+/// Rules that the answer to a text prompt, such as
+/// [`super::CommandType::PromptProjectName`], has to follow, given in a plugin with the
+/// `validation` field of the step. Answers that break a rule are refused, and the prompt
+/// is asked again.
+#[derive(
+    Clone, Debug, Default, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq,
+)]
+pub struct Validation {
+    /// A regular expression that the whole answer has to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern:    Option<Pattern>,
+    /// The fewest characters the answer can have.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_length: Option<usize>,
+    /// The most characters the answer can have.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+    /// Answers that can't be used, such as reserved names. Case is ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    forbidden:  Vec<String>,
+    /// What the user is told when the answer breaks a rule, instead of the rule it broke.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message:    Option<String>,
+}
+
+impl Validation {
+    /// Checks `answer` against every rule, returning what is wrong with it if it breaks
+    /// one of them.
+    pub fn check(&self, answer: &str) -> Result<(), String> {
+        let length = answer.chars().count();
+        let problem = if let Some(min) = self.min_length.filter(|min| length < *min) {
+            Some(format!("must be at least {min} characters long"))
+        } else if let Some(max) = self.max_length.filter(|max| length > *max) {
+            Some(format!("must be at most {max} characters long"))
+        } else if let Some(word) = self
+            .forbidden
+            .iter()
+            .find(|word| word.to_lowercase() == answer.to_lowercase())
+        {
+            Some(format!("\"{word}\" can't be used"))
+        } else {
+            self.pattern
+                .as_ref()
+                .filter(|pattern| !pattern.matches(answer))
+                .map(|pattern| format!("must match the pattern `{pattern}`"))
+        };
+
+        match (problem, &self.message) {
+            (None, _) => Ok(()),
+            (Some(_), Some(message)) => Err(message.clone()),
+            (Some(problem), None) => Err(problem),
+        }
+    }
+}
+
+/// A regular expression in a [`Validation`], given as a string in a plugin. The
+/// expression is checked when the plugin is loaded, so that a plugin with a broken
+/// pattern is reported rather than refusing every answer.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pattern(String);
+
+impl Pattern {
+    /// Whether the whole of `text` matches the pattern.
+    fn matches(&self, text: &str) -> bool {
+        compile(&self.0).is_ok_and(|regex| regex.is_match(text))
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        compile(&pattern)
+            .map(|_| Self(pattern.clone()))
+            .map_err(|error| format!("\"{pattern}\" is not a valid pattern: {error}"))
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self { pattern.0 }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+/// Compiles `pattern`, anchored so that it has to match all of the text rather than just
+/// part of it.
+fn compile(pattern: &str) -> Result<Regex, regex::Error> { Regex::new(&format!("^(?:{pattern})$")) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str) -> Option<Pattern> {
+        Some(Pattern::try_from(pattern.to_owned()).unwrap())
+    }
+
+    #[test]
+    fn accepts_anything_without_rules() {
+        assert_eq!(Validation::default().check(""), Ok(()));
+        assert_eq!(Validation::default().check("anything at all"), Ok(()));
+    }
+
+    #[test]
+    fn checks_the_length_in_characters() {
+        let validation = Validation {
+            min_length: Some(2),
+            max_length: Some(4),
+            ..Validation::default()
+        };
+
+        assert!(validation.check("a").is_err());
+        assert_eq!(validation.check("ab"), Ok(()));
+        assert_eq!(validation.check("éééé"), Ok(()));
+        assert_eq!(
+            validation.check("abcde"),
+            Err(String::from("must be at most 4 characters long"))
+        );
+    }
+
+    #[test]
+    fn refuses_forbidden_answers_ignoring_case() {
+        let validation = Validation {
+            forbidden: vec![String::from("std")],
+            ..Validation::default()
+        };
+
+        assert_eq!(
+            validation.check("STD"),
+            Err(String::from("\"std\" can't be used"))
+        );
+        assert_eq!(validation.check("stdx"), Ok(()));
+    }
+
+    #[test]
+    fn matches_the_whole_answer_against_the_pattern() {
+        let validation = Validation {
+            pattern: pattern("[a-z][a-z0-9_]*"),
+            ..Validation::default()
+        };
+
+        assert_eq!(validation.check("my_crate2"), Ok(()));
+        assert!(validation.check("2crate").is_err());
+        assert!(validation.check("my-crate").is_err());
+        assert!(Pattern::try_from(String::from("[a-z")).is_err());
+    }
+
+    #[test]
+    fn replaces_the_problem_with_the_message() {
+        let validation = Validation {
+            max_length: Some(3),
+            message: Some(String::from("Too long")),
+            ..Validation::default()
+        };
+
+        assert_eq!(validation.check("abcd"), Err(String::from("Too long")));
+        assert_eq!(validation.check("abc"), Ok(()));
+    }
+}
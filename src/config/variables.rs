@@ -0,0 +1,209 @@
+use std::{collections::BTreeMap, env, process::Command};
+
+use minijinja::{value::ValueKind, Environment, UndefinedBehavior, Value};
+
+use super::RunnerError;
+
+/// The named values that placeholders such as `{{project_name}}` in a
+/// [`super::CommandStep`] are resolved against when the step is run. Most values are
+/// strings, but they can also be lists that templates can loop over.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Variables {
+    values: BTreeMap<String, Value>,
+}
+
+impl Variables {
+    /// Creates a set of variables that contains the built-in values available to every
+    /// plugin: `date`, `year`, `user`, `git_user_name` and `git_user_email`. Values that
+    /// can't be determined on the current system are set to an empty string.
+    pub fn with_builtins() -> Self {
+        let now = chrono::Local::now();
+        let user = env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_default();
+
+        let mut variables = Self::default();
+        variables.set("date", now.format("%Y-%m-%d").to_string());
+        variables.set("year", now.format("%Y").to_string());
+        variables.set("user", user);
+        variables.set("git_user_name", git_config("user.name"));
+        variables.set("git_user_email", git_config("user.email"));
+
+        variables
+    }
+
+    pub fn set<V: Into<Value>>(&mut self, name: &str, value: V) {
+        self.values.insert(name.to_owned(), value.into());
+    }
+
+    /// Gets the value of the variable called `name`, if it's set and is a string.
+    pub fn get(&self, name: &str) -> Option<&str> { self.values.get(name)?.as_str() }
+
+    /// Gets the value of the variable called `name` as text, if it's set, so that answers
+    /// that aren't strings can be compared. Confirmations are `true` or `false`, and
+    /// other values are as `{{name}}` would render them, such as `["a", "b"]` for lists.
+    pub fn get_text(&self, name: &str) -> Option<String> {
+        let value = self.values.get(name)?;
+
+        match value.kind() {
+            ValueKind::Bool => Some(value.is_true().to_string()),
+            _ => Environment::new()
+                .render_str("{{ value }}", minijinja::context! { value })
+                .ok(),
+        }
+    }
+
+    /// Resolves every placeholder in `template` using the currently set variables.
+    /// Referring to a variable that isn't set is an error, rather than silently
+    /// resolving to nothing.
+    pub fn interpolate(&self, template: &str) -> std::result::Result<String, RunnerError> {
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+
+        environment
+            .render_str(template, &self.values)
+            .map_err(|error| RunnerError::CouldNotInterpolate {
+                template: template.to_owned(),
+                reason:   error.to_string(),
+            })
+    }
+
+    /// Renders the contents of a file from `template`, which can use the full template
+    /// syntax such as `{% if %}` and `{% for %}` blocks. Unlike [`Self::interpolate`],
+    /// the newline after a block tag is removed, so that blocks can be written on lines
+    /// of their own, and the trailing newline of the template is kept. `name` is used to
+    /// refer to the template in errors.
+    pub fn render(&self, name: &str, template: &str) -> std::result::Result<String, RunnerError> {
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+        environment.set_trim_blocks(true);
+        environment.set_lstrip_blocks(true);
+        environment.set_keep_trailing_newline(true);
+
+        environment
+            .template_from_named_str(name, template)
+            .and_then(|template| template.render(&self.values))
+            .map_err(|error| RunnerError::CouldNotRenderTemplate {
+                template: name.to_owned(),
+                reason:   error.to_string(),
+            })
+    }
+
+    /// Evaluates `expression` using the currently set variables, returning whether the
+    /// result is truthy.
+    pub fn evaluate(&self, expression: &str) -> std::result::Result<bool, RunnerError> {
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+
+        environment
+            .compile_expression(expression)
+            .and_then(|expression| expression.eval(&self.values))
+            .map(|value| value.is_true())
+            .map_err(|error| RunnerError::CouldNotInterpolate {
+                template: expression.to_owned(),
+                reason:   error.to_string(),
+            })
+    }
+}
+
+/// Reads `key` from the user's git configuration, returning an empty string if git isn't
+/// available or the key isn't set.
+fn git_config(key: &str) -> String {
+    Command::new("git")
+        .args(["config", "--get", key])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|value| value.trim().to_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> Variables {
+        let mut variables = Variables::default();
+        variables.set("project_name", "demo");
+        variables.set("lib", true);
+        variables.set("members", vec!["api", "worker"]);
+        variables
+    }
+
+    #[test]
+    fn interpolates_placeholders() {
+        let variables = variables();
+
+        assert_eq!(
+            variables
+                .interpolate("new {{project_name}} {{ project_name | upper }}")
+                .unwrap(),
+            "new demo DEMO"
+        );
+        assert_eq!(
+            variables
+                .interpolate("{% if lib %}--lib{% endif %}")
+                .unwrap(),
+            "--lib"
+        );
+        assert_eq!(
+            variables.interpolate("no placeholders").unwrap(),
+            "no placeholders"
+        );
+    }
+
+    #[test]
+    fn fails_on_undefined_variables() {
+        let variables = variables();
+
+        assert!(matches!(
+            variables.interpolate("new {{ project_nmae }}"),
+            Err(RunnerError::CouldNotInterpolate { template, .. }) if template == "new {{ project_nmae }}"
+        ));
+        assert!(matches!(
+            variables.render("README.md", "# {{ missing }}\n"),
+            Err(RunnerError::CouldNotRenderTemplate { template, .. }) if template == "README.md"
+        ));
+        assert!(variables.evaluate("missing == 'x'").is_err());
+        assert!(variables.interpolate("{{ project_name ").is_err());
+    }
+
+    #[test]
+    fn renders_blocks_on_lines_of_their_own() {
+        let variables = variables();
+
+        assert_eq!(
+            variables
+                .render(
+                    "members",
+                    "[\n{% for member in members %}\n  \"{{ member }}\",\n{% endfor %}\n]\n"
+                )
+                .unwrap(),
+            "[\n  \"api\",\n  \"worker\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn gets_values_as_text() {
+        let variables = variables();
+
+        assert_eq!(variables.get("project_name"), Some("demo"));
+        assert_eq!(variables.get("lib"), None);
+        assert_eq!(variables.get_text("lib").as_deref(), Some("true"));
+        assert_eq!(
+            variables.get_text("members").as_deref(),
+            Some(r#"["api", "worker"]"#)
+        );
+        assert_eq!(variables.get_text("missing"), None);
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let variables = variables();
+
+        assert!(variables.evaluate("project_name == 'demo'").unwrap());
+        assert!(variables.evaluate("'api' in members").unwrap());
+        assert!(!variables.evaluate("not lib").unwrap());
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::BTreeSet,
+    iter,
+    sync::mpsc::{self, Receiver, Sender, SyncSender},
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{self, Event};
+use proman::config::{Answer, Frontend, Question, RunningConfigMessage, Validation};
+
+/// How many events can be waiting to be handled before the threads that produce them have
+/// to wait for the main loop to catch up.
+const EVENT_CAPACITY: usize = 1024;
+
+/// The most events that are handled between two draws, so that the screen is still
+/// redrawn while a command is printing a lot of output.
+const MAX_EVENTS_PER_FRAME: usize = 256;
+
+/// Something that the main loop has to respond to.
+#[derive(Clone, Debug)]
+pub(crate) enum AppEvent {
+    /// An event from the terminal, such as a key press, a paste or a resize.
+    Terminal(Event),
+    /// A message from the run that is in progress.
+    Runner(RunningConfigMessage),
+    /// A question from the run that is in progress, which waits for an answer to be sent
+    /// down the channel. Dropping the channel without answering cancels the run.
+    Prompt(Question, Sender<Answer>),
+    /// Nothing happened for a while.
+    Tick,
+}
+
+/// Brings the events from the terminal and from the run that is in progress together, so
+/// that the main loop can wait on all of them at once without blocking on any one.
+pub(crate) struct Events {
+    sender:    SyncSender<AppEvent>,
+    receiver:  Receiver<AppEvent>,
+    tick_rate: Duration,
+}
+
+impl Events {
+    /// Starts reading events from the terminal. If nothing happens for `tick_rate`, an
+    /// [`AppEvent::Tick`] is given instead.
+    pub(crate) fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(EVENT_CAPACITY);
+        let terminal_sender = sender.clone();
+
+        thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                if terminal_sender.send(AppEvent::Terminal(event)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            sender,
+            receiver,
+            tick_rate,
+        }
+    }
+
+    /// A frontend for a run, which passes the messages and questions of the run on to the
+    /// main loop.
+    pub(crate) fn frontend(&self) -> TuiFrontend {
+        TuiFrontend {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Waits up to the tick rate for an event, then takes any others that are already
+    /// waiting, up to [`MAX_EVENTS_PER_FRAME`] in total.
+    pub(crate) fn next_batch(&self) -> impl Iterator<Item = AppEvent> + '_ {
+        let first = self
+            .receiver
+            .recv_timeout(self.tick_rate)
+            .unwrap_or(AppEvent::Tick);
+
+        iter::once(first)
+            .chain(iter::from_fn(|| self.receiver.try_recv().ok()).take(MAX_EVENTS_PER_FRAME - 1))
+    }
+}
+
+/// The [`Frontend`] of runs started from the TUI. The run is on a thread of its own, so
+/// each question waits for the main loop to send back the answer.
+pub(crate) struct TuiFrontend {
+    sender: SyncSender<AppEvent>,
+}
+
+impl TuiFrontend {
+    fn ask(&mut self, question: Question) -> Option<Answer> {
+        let (answer_tx, answer_rx) = mpsc::channel();
+
+        self.sender
+            .send(AppEvent::Prompt(question, answer_tx))
+            .ok()?;
+        answer_rx.recv().ok()
+    }
+}
+
+impl Frontend for TuiFrontend {
+    fn ask_text(
+        &mut self,
+        label: &str,
+        default: Option<&str>,
+        validation: &Validation,
+    ) -> Option<String> {
+        let question = Question::Text {
+            label:      label.to_owned(),
+            default:    default.map(str::to_owned),
+            validation: validation.clone(),
+        };
+
+        match self.ask(question.clone())? {
+            answer @ Answer::Text(_) => Some(question.value_of(answer).to_string()),
+            _ => None,
+        }
+    }
+
+    fn ask_choice(&mut self, label: &str, options: &[String], default: usize) -> Option<usize> {
+        let question = Question::Choice {
+            label: label.to_owned(),
+            options: options.to_vec(),
+            default,
+        };
+
+        match self.ask(question)? {
+            Answer::Choice(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    fn ask_confirm(&mut self, label: &str, default: bool) -> Option<bool> {
+        let question = Question::Confirm {
+            label: label.to_owned(),
+            default,
+        };
+
+        match self.ask(question)? {
+            Answer::Confirm(confirmed) => Some(confirmed),
+            _ => None,
+        }
+    }
+
+    fn ask_multi_select(
+        &mut self,
+        label: &str,
+        options: &[String],
+        default: &BTreeSet<usize>,
+    ) -> Option<BTreeSet<usize>> {
+        let question = Question::MultiSelect {
+            label:   label.to_owned(),
+            options: options.to_vec(),
+            default: default.clone(),
+        };
+
+        match self.ask(question)? {
+            Answer::MultiSelect(selected) => Some(selected),
+            _ => None,
+        }
+    }
+
+    fn show(&mut self, message: RunningConfigMessage) {
+        _ = self.sender.send(AppEvent::Runner(message));
+    }
+}
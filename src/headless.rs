@@ -0,0 +1,282 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use proman::config::{
+    self, Answers, Error, Frontend, LanguageConfig, LanguageConfigRunner, PlannedAction,
+    PluginLoader, RunnerError, RunningConfigMessage, Validation,
+};
+
+use crate::cli;
+
+/// Runs the plugin for `language` in dry-run mode without the TUI, then prints the plan
+/// of everything the run would have done.
+pub(crate) fn dry_run(
+    loader: &PluginLoader,
+    language: &str,
+    answers: Answers,
+) -> config::Result<()> {
+    let config = loader.load()?.find(language)?;
+    let runner = config
+        .create_runner()
+        .with_dry_run(true)
+        .with_answers(answers);
+    let plan = run(runner, true)?;
+
+    print_plan(&config, &plan);
+
+    Ok(())
+}
+
+/// Prints the plan of everything a dry run of `config` would have done.
+pub(crate) fn print_plan(config: &LanguageConfig, plan: &[PlannedAction]) {
+    println!();
+    println!("Execution plan for {}:", config.language());
+
+    if plan.is_empty() {
+        println!("  (nothing would be done)");
+    }
+
+    for (index, action) in plan.iter().enumerate() {
+        println!("  {}. {action}", index + 1);
+    }
+}
+
+/// Runs `runner` without the TUI, printing the output of the run to stdout. If
+/// `interactive` is set, questions are asked on stdin; otherwise, a step that fails with
+/// [`config::FailurePolicy::Ask`] stops the run, and its changes are kept. Returns the
+/// actions that were planned, which are only reported in dry-run mode.
+///
+/// Unless the run was cancelled, saving the answers that were given is offered
+/// afterwards.
+pub(crate) fn run(
+    runner: LanguageConfigRunner,
+    interactive: bool,
+) -> config::Result<Vec<PlannedAction>> {
+    let mut frontend = StdioFrontend {
+        interactive,
+        ..Default::default()
+    };
+    let runner = runner.with_interactive(interactive);
+    let result = runner.run(&mut frontend);
+
+    // a question that couldn't be asked cancels the run, but the reason it couldn't be
+    // asked is more useful than the run being cancelled.
+    if let Some(error) = frontend.error {
+        return Err(Error::IO(error));
+    }
+
+    if !matches!(result, Err(RunnerError::Cancelled)) {
+        cli::offer_to_save_answers(&mut frontend, &runner);
+    }
+
+    result?;
+
+    Ok(frontend.plan)
+}
+
+/// Asks the questions of a run on stdin and prints its progress to stdout.
+#[derive(Debug, Default)]
+struct StdioFrontend {
+    /// Whether questions are asked, rather than left unanswered.
+    interactive: bool,
+    /// The actions that the run would take, reported when it's in dry-run mode.
+    plan:        Vec<PlannedAction>,
+    /// Why stdin couldn't be read, if it couldn't be.
+    error:       Option<io::Error>,
+}
+
+impl StdioFrontend {
+    /// Asks a question with `ask` if questions are being asked, keeping the error if
+    /// stdin couldn't be read.
+    fn answer<T>(&mut self, ask: impl FnOnce() -> io::Result<T>) -> Option<T> {
+        if !self.interactive {
+            return None;
+        }
+
+        ask().map_err(|error| self.error = Some(error)).ok()
+    }
+}
+
+impl Frontend for StdioFrontend {
+    fn ask_text(
+        &mut self,
+        label: &str,
+        default: Option<&str>,
+        validation: &Validation,
+    ) -> Option<String> {
+        self.answer(|| ask_text(label, default, validation))
+    }
+
+    fn ask_choice(&mut self, label: &str, options: &[String], default: usize) -> Option<usize> {
+        self.answer(|| choose(label, options, Some(default)))
+    }
+
+    fn ask_confirm(&mut self, label: &str, default: bool) -> Option<bool> {
+        self.answer(|| confirm(label, default))
+    }
+
+    fn ask_multi_select(
+        &mut self,
+        label: &str,
+        options: &[String],
+        default: &BTreeSet<usize>,
+    ) -> Option<BTreeSet<usize>> {
+        self.answer(|| select(label, options, default))
+    }
+
+    fn show(&mut self, message: RunningConfigMessage) {
+        match message {
+            RunningConfigMessage::SetCommandStepText(text) => println!("==> {text}"),
+            RunningConfigMessage::StepSkipped(name) => println!("==> {name} (skipped)"),
+            RunningConfigMessage::AlreadyAnswered { label, answer } =>
+                println!("{label}: {answer} (already answered)"),
+            RunningConfigMessage::CommandOutput(output) => println!("{output}"),
+            RunningConfigMessage::CommandExited(status) => println!("Command exited with {status}"),
+            RunningConfigMessage::Planned(action) => {
+                println!("Would {action}");
+                self.plan.push(action);
+            },
+            RunningConfigMessage::StepFailed(error) => eprintln!("Step failed: {error}"),
+            RunningConfigMessage::Effects(effects) => {
+                println!("The run made these changes:");
+                effects.iter().for_each(|effect| println!("  {effect}"));
+            },
+            RunningConfigMessage::Failed(RunnerError::UnmetRequirements(unmet)) =>
+                unmet.iter().for_each(|unmet| eprintln!("  {unmet}")),
+            RunningConfigMessage::Failed(_) | RunningConfigMessage::Finished => (),
+        }
+    }
+}
+
+/// Asks for a line of input on stdin, returning it without the trailing newline.
+fn ask(label: &str) -> io::Result<String> {
+    print!("{label}: ");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("stdin closed before \"{label}\" was answered"),
+        ));
+    }
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Asks the user to choose one of `options` by number, asking again until a valid choice
+/// is given. If there is a `default`, an empty answer chooses it. Returns the index of
+/// the chosen option.
+fn choose<T: fmt::Display>(
+    label: &str,
+    options: &[T],
+    default: Option<usize>,
+) -> io::Result<usize> {
+    println!("{label}");
+
+    for (index, option) in options.iter().enumerate() {
+        println!("  {}. {option}", index + 1);
+    }
+
+    let prompt = match default {
+        Some(default) => format!("Choice [{}]", default + 1),
+        None => String::from("Choice"),
+    };
+
+    loop {
+        let choice = ask(&prompt)?;
+
+        match (choice.trim().parse::<usize>(), default) {
+            (Ok(choice), _) if (1..=options.len()).contains(&choice) => return Ok(choice - 1),
+            (_, Some(default)) if choice.trim().is_empty() => return Ok(default),
+            _ => println!("Please enter a number between 1 and {}", options.len()),
+        }
+    }
+}
+
+/// Asks for a line of text, asking again until the answer passes `validation`. An empty
+/// answer is replaced with the `default`, if there is one.
+fn ask_text(label: &str, default: Option<&str>, validation: &Validation) -> io::Result<String> {
+    let prompt = match default {
+        Some(default) => format!("{label} [{default}]"),
+        None => label.to_owned(),
+    };
+
+    loop {
+        let answer = match (ask(&prompt)?, default) {
+            (answer, Some(default)) if answer.is_empty() => default.to_owned(),
+            (answer, _) => answer,
+        };
+
+        match validation.check(&answer) {
+            Ok(()) => return Ok(answer),
+            Err(reason) => println!("Invalid answer: {reason}"),
+        }
+    }
+}
+
+/// Asks a yes or no question, asking again until it's answered with one of them. An
+/// empty answer gives the `default`.
+fn confirm(label: &str, default: bool) -> io::Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+
+    loop {
+        match ask(&format!("{label} [{hint}]"))?
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer yes or no"),
+        }
+    }
+}
+
+/// Asks the user to choose any number of `options` by number, separated by commas. An
+/// empty answer chooses the options at `default`.
+fn select(
+    label: &str,
+    options: &[String],
+    default: &BTreeSet<usize>,
+) -> io::Result<BTreeSet<usize>> {
+    println!("{label}");
+
+    for (index, option) in options.iter().enumerate() {
+        println!("  {}. {option}", index + 1);
+    }
+
+    let default_choices = default
+        .iter()
+        .map(|index| (index + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    loop {
+        let choices = ask(&format!("Choices, separated by commas [{default_choices}]"))?;
+
+        if choices.trim().is_empty() {
+            return Ok(default.clone());
+        }
+
+        let selected = choices
+            .split(',')
+            .map(|choice| match choice.trim().parse::<usize>() {
+                Ok(choice) if (1..=options.len()).contains(&choice) => Ok(choice - 1),
+                _ => Err(choice.trim().to_owned()),
+            })
+            .collect::<Result<_, _>>();
+
+        match selected {
+            Ok(selected) => return Ok(selected),
+            Err(choice) => println!(
+                "\"{choice}\" isn't a number between 1 and {}",
+                options.len()
+            ),
+        }
+    }
+}
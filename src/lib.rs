@@ -0,0 +1,54 @@
+//! The library behind `proman`, which loads language plugins and runs them to create new
+//! projects. A run asks its questions and shows its progress through a
+//! [`config::Frontend`], so plugins can be run from something other than the TUI:
+//!
+//! ```
+//! use std::collections::BTreeSet;
+//!
+//! use proman::config::{
+//!     find_language_config, Answers, Frontend, GivenAnswer, RunningConfigMessage, Validation,
+//! };
+//!
+//! /// A frontend that can't answer any questions, and prints what a dry run would do.
+//! struct PrintPlan;
+//!
+//! impl Frontend for PrintPlan {
+//!     fn ask_text(&mut self, _: &str, _: Option<&str>, _: &Validation) -> Option<String> { None }
+//!
+//!     fn ask_choice(&mut self, _: &str, _: &[String], _: usize) -> Option<usize> { None }
+//!
+//!     fn ask_multi_select(
+//!         &mut self,
+//!         _: &str,
+//!         _: &[String],
+//!         _: &BTreeSet<usize>,
+//!     ) -> Option<BTreeSet<usize>> {
+//!         None
+//!     }
+//!
+//!     fn show(&mut self, message: RunningConfigMessage) {
+//!         if let RunningConfigMessage::Planned(action) = message {
+//!             println!("would {action}");
+//!         }
+//!     }
+//! }
+//!
+//! let mut answers = Answers::new();
+//! answers.insert("project_name", GivenAnswer::Text(String::from("example")));
+//! answers.insert("project_type", GivenAnswer::Text(String::from("Binary")));
+//!
+//! let config = find_language_config("rust")?;
+//! let runner = config
+//!     .create_runner()
+//!     .with_dry_run(true)
+//!     .with_interactive(false)
+//!     .with_answers(answers);
+//!
+//! runner.run(&mut PrintPlan)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#![allow(clippy::pedantic, clippy::nursery)]
+
+pub mod config;
+mod consts;
@@ -1,351 +1,710 @@
 #![allow(clippy::pedantic, clippy::nursery)]
-#![feature(let_chains)]
 
-use std::{collections::BTreeSet, io::stdout, time::Duration};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    env,
+    io::stdout,
+    process::ExitCode,
+    sync::mpsc::Sender,
+    thread,
+    time::Duration,
+};
 
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{
     backend::CrosstermBackend,
     prelude::*,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Paragraph, Wrap},
     Terminal,
 };
 
+use proman::config::{
+    self, Answer, Answers, LanguageConfig, LanguageConfigRunner, PluginDiagnostic, PluginLoader,
+    PluginSource, Question, RunnerError, RunningConfigMessage,
+};
+
 use crate::{
-    config::{
-        parse_language_configs, LanguageConfig, LanguageConfigRunner, ProjectType,
-        RunningConfigMessage,
-    },
-    widgets::{StatefulList, StatefulListItem},
+    cli::{Cli, Command},
+    events::{AppEvent, Events},
+    widgets::{InputOutcome, StatefulList, StatefulListItem, TextInput},
 };
 
-mod config;
-mod consts;
+mod cli;
+mod events;
+mod headless;
 mod widgets;
 
 // The cleanup process for exiting the application.
 fn cleanup() -> config::Result<()> {
     disable_raw_mode()?;
+    stdout().execute(DisableBracketedPaste)?;
     stdout().execute(LeaveAlternateScreen)?;
 
     Ok(())
 }
 
+/// How long the main loop waits for an event before drawing the screen again anyway.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// The most lines of output kept for the running screen.
+const MAX_SCROLL_BACK: usize = 1000;
+
 enum Message {
     ShouldQuit,
+    /// Cancel the run that is in progress, which offers to roll back what it did.
+    CancelRun,
     RunConfiguration(usize),
+    DryRunConfiguration(usize),
+    /// Add members to the project in the current directory with the configuration.
+    AddToProject(usize),
     NoOp,
 }
 
-#[derive(Clone, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
-enum InputMode {
-    #[default]
-    None,
-    Text,
-    Choice,
+/// The status of a step shown in the step log of the running screen.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum StepStatus {
+    Running,
+    Done,
+    Skipped,
+    Failed,
+}
+
+impl StepStatus {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Running => "..",
+            Self::Done => "ok",
+            Self::Skipped => "--",
+            Self::Failed => "!!",
+        }
+    }
+}
+
+/// A language shown in the list on the main screen, along with where the plugins that
+/// its plugin overrides came from.
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+struct LanguageItem(LanguageConfig, Vec<PluginSource>);
+
+impl<'a> From<LanguageItem> for Text<'a> {
+    fn from(LanguageItem(config, shadowed): LanguageItem) -> Text<'a> {
+        let mut source = config
+            .source()
+            .map(|source| format!("  {source}"))
+            .unwrap_or_default();
+
+        for shadowed in shadowed {
+            source.push_str(&format!(", overrides {shadowed}"));
+        }
+
+        Text::from(Line::from(vec![
+            Span::raw(config.language().to_owned()),
+            Span::styled(source, Style::default().fg(Color::DarkGray)),
+        ]))
+    }
+}
+
+/// An option of a prompt, ordered by where it is in the prompt rather than by its
+/// text.
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+struct PromptOption {
+    index: usize,
+    text:  String,
+}
+
+impl<'a> From<PromptOption> for Text<'a> {
+    fn from(option: PromptOption) -> Text<'a> { Text::from(option.text) }
+}
+
+/// Builds the list of options shown for a prompt. If `checked` is given, each option is
+/// shown with a checkbox.
+fn prompt_options(options: &[String], checked: Option<&BTreeSet<usize>>) -> BTreeSet<PromptOption> {
+    options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| PromptOption {
+            index,
+            text: match checked {
+                Some(checked) if checked.contains(&index) => format!("[x] {option}"),
+                Some(_) => format!("[ ] {option}"),
+                None => option.clone(),
+            },
+        })
+        .collect()
 }
 
 #[derive(Clone, Debug, Default)]
 struct RunningState {
-    step_name:              String,
-    scroll_back:            Vec<String>,
-    input_mode:             InputMode,
-    input:                  String,
-    project_type_list:      Option<StatefulList<ProjectType>>,
-    selected_project_type:  Option<ProjectType>,
-    running_config_message: RunningConfigMessage,
+    step_name:      String,
+    step_log:       Vec<(String, StepStatus)>,
+    /// The most recent lines of output, up to [`MAX_SCROLL_BACK`].
+    scroll_back:    VecDeque<String>,
+    /// The question that is being asked, and where to send the answer.
+    prompt:         Option<(Question, Sender<Answer>)>,
+    input:          TextInput,
+    prompt_list:    Option<StatefulList<PromptOption>>,
+    prompt_checked: BTreeSet<usize>,
+    /// Why the text that was entered can't be submitted, shown under the input.
+    input_error:    Option<String>,
+    failure:        Option<String>,
+    /// Whether the last message of the run has been received.
+    finished:       bool,
 }
 
+#[allow(clippy::large_enum_variant)]
 enum AppState<ListItem>
 where
     for<'a> ListItem: StatefulListItem<'a>,
 {
     Main(StatefulList<ListItem>),
-    Starting(LanguageConfig),
-    Running(LanguageConfigRunner, Option<RunningState>),
-    Stopping,
+    Running(LanguageConfigRunner, RunningState),
 }
 
-fn main() -> config::Result<()> {
+fn main() -> ExitCode {
+    let cli = Cli::parse_args();
+    let loader = match cli.plugin_loader() {
+        Ok(loader) => loader,
+        Err(error) => return cli::report(&error),
+    };
+
+    let result = match (cli.command, cli.dry_run) {
+        (Some(Command::New(args)), _) => {
+            handle_signals(false);
+            return cli::new(args, &loader);
+        },
+        (Some(Command::Add(args)), _) => {
+            handle_signals(false);
+            return cli::add(args, &loader);
+        },
+        (Some(Command::Plugins(args)), _) => return cli::plugins(args, &loader),
+        (None, Some(language)) => {
+            handle_signals(false);
+            cli::load_answers(cli.answers.as_deref())
+                .and_then(|answers| headless::dry_run(&loader, &language, answers))
+        },
+        (None, None) =>
+            cli::load_answers(cli.answers.as_deref()).and_then(|answers| run_tui(&loader, answers)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => cli::report(&error),
+    }
+}
+
+/// Runs the TUI with the plugins loaded by `loader` until the user quits. Prompts that
+/// are answered by `answers` aren't asked.
+fn run_tui(loader: &PluginLoader, answers: Answers) -> config::Result<()> {
+    let report = loader.load()?;
+
+    handle_signals(true);
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableBracketedPaste)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
-    let language_configs = match parse_language_configs() {
-        Ok(c) => c,
-        Err(_error) => {
-            cleanup().unwrap();
-            panic!("could not parse language configs: {_error}")
-        },
-    };
-    let language_list = StatefulList::new(language_configs.clone());
+    let diagnostics = report.diagnostics().to_vec();
+    let language_list = StatefulList::new(
+        report
+            .configs()
+            .iter()
+            .map(|config| LanguageItem(config.clone(), report.shadowed(config).to_vec()))
+            .collect::<BTreeSet<_>>(),
+    );
+    let language_configs = report.into_configs();
     let mut state = AppState::Main(language_list);
 
     let language_configs = language_configs.iter().collect::<Vec<_>>();
+    let events = Events::new(TICK_RATE);
 
     let mut should_quit = false;
     while !should_quit {
-        terminal.draw(|frame: &mut Frame| ui(frame, &mut state))?;
+        terminal.draw(|frame: &mut Frame| ui(frame, &mut state, &diagnostics))?;
+
+        for event in events.next_batch() {
+            let message = match event {
+                AppEvent::Terminal(event) => handle_events(&mut state, &event)?,
+                AppEvent::Runner(message) => {
+                    handle_runner_message(&mut state, message);
+                    Message::NoOp
+                },
+                AppEvent::Prompt(question, channel) => {
+                    start_prompt(&mut state, question, channel);
+                    Message::NoOp
+                },
+                AppEvent::Tick => Message::NoOp,
+            };
 
-        match handle_events(&mut state)? {
-            Message::ShouldQuit => should_quit = true,
-            Message::RunConfiguration(index) => match language_configs.get(index) {
-                Some(config) => state = AppState::Starting(<LanguageConfig>::clone(config)),
-                None => panic!("somehow got an out of bounds index for running a configuration"),
-            },
-            _ => (),
+            match message {
+                Message::ShouldQuit => should_quit = true,
+                Message::CancelRun =>
+                    if let AppState::Running(ref runner, _) = state {
+                        runner.cancel();
+                    },
+                Message::RunConfiguration(index)
+                | Message::DryRunConfiguration(index)
+                | Message::AddToProject(index) => {
+                    let dry_run = matches!(message, Message::DryRunConfiguration(..));
+                    let Some(config) = language_configs.get(index) else {
+                        panic!("somehow got an out of bounds index for running a configuration")
+                    };
+
+                    let mut runner = config
+                        .create_runner()
+                        .with_dry_run(dry_run)
+                        .with_answers(answers.clone());
+                    if let Message::AddToProject(..) = message {
+                        runner = runner.with_existing_project(env::current_dir()?);
+                    }
+                    let mut frontend = events.frontend();
+                    let run = runner.clone();
+
+                    // the run fails by showing the failure, so its result is only needed to
+                    // know whether it was cancelled.
+                    thread::spawn(move || {
+                        if !matches!(run.run(&mut frontend), Err(RunnerError::Cancelled)) {
+                            cli::offer_to_save_answers(&mut frontend, &run);
+                        }
+                    });
+                    state = AppState::Running(runner, RunningState::default());
+                },
+                Message::NoOp => (),
+            }
+
+            if should_quit {
+                break;
+            }
         }
     }
 
+    config::kill_running_commands();
     cleanup()
 }
 
-// Extracts a [`RunningState`] from a given reference to [`Option<RunningState>`]. This
-// isn't technically needed, but Clippy complains about duplicated code.
-fn extract_state(state: &Option<RunningState>) -> RunningState {
-    if let Some(state) = state {
-        state.clone()
-    } else {
-        RunningState {
-            project_type_list: Some(StatefulList::new(BTreeSet::<ProjectType>::new())),
-            ..Default::default()
+/// Makes sure that being interrupted or terminated doesn't leave behind any commands that
+/// are still running, or the terminal in raw mode if `restore_terminal` is set.
+fn handle_signals(restore_terminal: bool) {
+    _ = ctrlc::set_handler(move || {
+        config::kill_running_commands();
+
+        if restore_terminal {
+            _ = cleanup();
         }
-    }
+
+        std::process::exit(130);
+    });
 }
 
-/// Higher-order scaffolding function for handling key events in [`handle_events`].
-fn key_handler<T, F>(param: T, f: Box<F>) -> config::Result<Message>
-where
-    F: FnOnce(T, KeyCode) -> config::Result<Message>,
-{
-    if event::poll(Duration::from_millis(50))? {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                return f(param, key.code);
-            }
+impl RunningState {
+    /// Marks the step that is currently running, if there is one, with the given status.
+    fn finish_running_step(&mut self, status: StepStatus) {
+        if let Some((_, step_status @ StepStatus::Running)) = self.step_log.last_mut() {
+            *step_status = status;
         }
     }
 
-    Ok(Message::NoOp)
+    /// Adds a line to the scroll-back, forgetting the oldest line if it's full.
+    fn log(&mut self, line: String) {
+        if self.scroll_back.len() >= MAX_SCROLL_BACK {
+            self.scroll_back.pop_front();
+        }
+
+        self.scroll_back.push_back(line);
+    }
+
+    /// Puts away the prompt that is being asked, once it's been answered or cancelled. If
+    /// it was cancelled, dropping its channel stops the run.
+    fn finish_prompt(&mut self) {
+        self.prompt = None;
+        self.input.reset();
+        self.input_error = None;
+        self.prompt_list = None;
+        self.prompt_checked.clear();
+    }
 }
 
-/// Handle [`InputMode::Text`] input mode events for use in [`handle_events`].
-fn handle_text_input_mode_events(
-    key_code: KeyCode,
-    state: &mut RunningState,
-) -> config::Result<Message> {
-    match key_code {
-        KeyCode::Char(character) => {
-            state.input.push(character);
-            Ok(Message::NoOp)
-        },
-        KeyCode::Esc => Ok(Message::ShouldQuit),
-        _ => Ok(Message::NoOp),
+/// The key that was pressed, if `event` is a key press.
+fn key_code(event: &Event) -> Option<KeyCode> {
+    match event {
+        Event::Key(key) => Some(key.code),
+        _ => None,
     }
 }
 
-/// Handle [`InputMode::Choice`] input mode events for use in [`handle_events`].
-fn handle_choice_input_mode_events(
-    key_code: KeyCode,
-    state: &mut RunningState,
-) -> config::Result<Message> {
-    let RunningConfigMessage::PromptForProjectType { ref channel, .. } =
-        state.running_config_message
-    else {
+/// Handle events while a question of the run is being asked, for use in
+/// [`handle_events`]. Pressing escape cancels the prompt, which stops the run.
+fn handle_prompt_events(event: &Event, state: &mut RunningState) -> config::Result<Message> {
+    let Some((ref question, ref channel)) = state.prompt else {
         unreachable!("already checked");
     };
 
-    if let Some(ref selected_project_type) = state.selected_project_type {
-        channel.send(selected_project_type.clone()).unwrap();
+    let answer = match (question, event) {
+        (Question::Text { .. }, _) => match state.input.handle_event(event) {
+            InputOutcome::Submitted => Some(Answer::Text(state.input.value().to_owned())),
+            InputOutcome::Cancelled => {
+                state.finish_prompt();
+                return Ok(Message::NoOp);
+            },
+            InputOutcome::Changed => {
+                // once an answer has been refused, the error follows the input as it's
+                // edited.
+                if state.input_error.is_some() {
+                    state.input_error = question
+                        .check(&Answer::Text(state.input.value().to_owned()))
+                        .err();
+                }
+
+                None
+            },
+            InputOutcome::Ignored => None,
+        },
+        (_, Event::Key(key)) => match (question, key.code) {
+            (_, KeyCode::Esc) => {
+                state.finish_prompt();
+                return Ok(Message::NoOp);
+            },
+            (Question::Confirm { .. }, KeyCode::Char('y')) => Some(Answer::Confirm(true)),
+            (Question::Confirm { .. }, KeyCode::Char('n')) => Some(Answer::Confirm(false)),
+            (Question::MultiSelect { options, .. }, KeyCode::Char(' ')) => {
+                if let Some(ref mut list) = state.prompt_list {
+                    let index = list.get_selected_index();
 
-        return Ok(Message::NoOp);
-    }
+                    if !state.prompt_checked.remove(&index) {
+                        state.prompt_checked.insert(index);
+                    }
 
-    let mut list = state.project_type_list.clone().unwrap();
+                    list.set_items(prompt_options(options, Some(&state.prompt_checked)));
+                }
 
-    match key_code {
-        KeyCode::Char('k') | KeyCode::Up => {
-            list.previous_item();
-            Ok(Message::NoOp)
-        },
-        KeyCode::Char('j') | KeyCode::Down => {
-            list.next_item();
-            Ok(Message::NoOp)
+                None
+            },
+            (_, KeyCode::Char('k') | KeyCode::Up) => {
+                state.prompt_list.as_mut().map(StatefulList::previous_item);
+                None
+            },
+            (_, KeyCode::Char('j') | KeyCode::Down) => {
+                state.prompt_list.as_mut().map(StatefulList::next_item);
+                None
+            },
+            (_, KeyCode::Enter) => {
+                let index = state
+                    .prompt_list
+                    .as_ref()
+                    .map_or(0, StatefulList::get_selected_index);
+
+                Some(match question {
+                    Question::Confirm { .. } => Answer::Confirm(index == 0),
+                    Question::MultiSelect { .. } =>
+                        Answer::MultiSelect(state.prompt_checked.clone()),
+                    _ => Answer::Choice(index),
+                })
+            },
+            _ => None,
         },
-        KeyCode::Enter => {
-            let selected_index = list.get_selected_index();
-            if let Some(selected_type) = list.get_items().get(selected_index) {
-                channel.send(selected_type.clone()).unwrap();
+        _ => None,
+    };
 
-                return Ok(Message::NoOp);
-            }
+    if let Some(answer) = answer {
+        if let Err(reason) = question.check(&answer) {
+            state.input_error = Some(reason);
+            return Ok(Message::NoOp);
+        }
 
-            Ok(Message::NoOp)
-        },
-        _ => Ok(Message::NoOp),
+        _ = channel.send(answer);
+        state.finish_prompt();
     }
+
+    Ok(Message::NoOp)
 }
 
 /// Handle events that happen during the runtime of the application, can include key
 /// events, or other custom-made events that the application should be able to respond to.
-fn handle_events<ListItem>(app_state: &mut AppState<ListItem>) -> config::Result<Message>
+fn handle_events<ListItem>(
+    app_state: &mut AppState<ListItem>,
+    event: &Event,
+) -> config::Result<Message>
 where
     for<'a> ListItem: StatefulListItem<'a>,
 {
-    match app_state {
-        AppState::Main(ref mut language_list) => key_handler(
-            language_list,
-            Box::new(
-                |list: &mut StatefulList<ListItem>, key_code| match key_code {
-                    KeyCode::Char('q') => Ok(Message::ShouldQuit),
-                    KeyCode::Char('k') | KeyCode::Up => {
-                        list.previous_item();
-                        Ok(Message::NoOp)
-                    },
-                    KeyCode::Char('j') | KeyCode::Down => {
-                        list.next_item();
-                        Ok(Message::NoOp)
-                    },
-                    KeyCode::Enter => Ok(Message::RunConfiguration(list.get_selected_index())),
-                    _ => Ok(Message::NoOp),
-                },
-            ),
-        ),
-        AppState::Running(_, running_state) => {
-            let mut state = extract_state(running_state);
-
-            key_handler(
-                running_state,
-                Box::new(|running_state: &mut Option<RunningState>, key_code| {
-                    let message = match state.input_mode {
-                        InputMode::Text => handle_text_input_mode_events(key_code, &mut state),
-                        InputMode::Choice
-                            if matches!(
-                                state,
-                                RunningState {
-                                    running_config_message:
-                                        RunningConfigMessage::PromptForProjectType { .. },
-                                    project_type_list: Some(..),
-                                    ..
-                                }
-                            ) =>
-                            handle_choice_input_mode_events(key_code, &mut state),
-                        _ => match key_code {
-                            KeyCode::Char('q') => Ok(Message::ShouldQuit),
-                            _ => Ok(Message::NoOp),
-                        },
-                    };
+    if let Event::Key(key) = event {
+        if key.kind != KeyEventKind::Press {
+            return Ok(Message::NoOp);
+        }
+    }
 
-                    *running_state = Some(state);
+    match app_state {
+        AppState::Main(ref mut list) => Ok(match key_code(event) {
+            Some(KeyCode::Char('q')) => Message::ShouldQuit,
+            Some(KeyCode::Char('k') | KeyCode::Up) => {
+                list.previous_item();
+                Message::NoOp
+            },
+            Some(KeyCode::Char('j') | KeyCode::Down) => {
+                list.next_item();
+                Message::NoOp
+            },
+            Some(KeyCode::Enter) => Message::RunConfiguration(list.get_selected_index()),
+            Some(KeyCode::Char('d')) => Message::DryRunConfiguration(list.get_selected_index()),
+            Some(KeyCode::Char('a')) => Message::AddToProject(list.get_selected_index()),
+            _ => Message::NoOp,
+        }),
+        AppState::Running(_, state) => {
+            // only text inputs do anything with pasted text.
+            let key_code = key_code(event).unwrap_or(KeyCode::Null);
+
+            if state.prompt.is_some() {
+                return handle_prompt_events(event, state);
+            }
 
-                    message
-                }),
-            )
+            // quitting while the run is in progress cancels it, so that what it did can
+            // still be rolled back.
+            match key_code {
+                KeyCode::Char('q') if state.finished => Ok(Message::ShouldQuit),
+                KeyCode::Char('q' | 'c') | KeyCode::Esc if !state.finished =>
+                    Ok(Message::CancelRun),
+                _ => Ok(Message::NoOp),
+            }
         },
-        _ => Ok(Message::NoOp),
     }
 }
 
-fn ui_running<ListItem>(
-    frame: &mut Frame,
-    runner: &mut LanguageConfigRunner,
-    running_state: &mut Option<RunningState>,
-) -> Option<AppState<ListItem>>
-where
+/// Updates the running screen with a message from the run. The running screen stays up
+/// once the run has finished, so that its output, or the reason it failed, can be read.
+fn handle_runner_message<ListItem>(
+    app_state: &mut AppState<ListItem>,
+    message: RunningConfigMessage,
+) where
     for<'a> ListItem: StatefulListItem<'a>,
 {
-    let mut state = extract_state(running_state);
-
-    let layout_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(98), Constraint::Fill(2)])
-        .margin(2)
-        .split(frame.size());
+    let AppState::Running(_, ref mut state) = app_state else {
+        return;
+    };
 
-    let mut res = runner.start_or_continue();
-    if let Ok(ref mut rx) = res {
-        if let Ok((message, should_stop)) = rx.recv() {
-            // TODO: handle user input for the project name and project type
-            match message {
-                RunningConfigMessage::SetCommandStepText(text) => state.step_name = text,
-                RunningConfigMessage::CommandOutput(output) => {
-                    state.scroll_back.push(output);
-                },
-                RunningConfigMessage::StartInputPrompt => {
-                    state.input_mode = InputMode::Text;
-                },
-                RunningConfigMessage::StartChoicePrompt => {
-                    state.input_mode = InputMode::Choice;
-                },
-                RunningConfigMessage::PromptForProjectName(name_tx) => {},
-                RunningConfigMessage::PromptForProjectType {
-                    available_types, ..
-                } if available_types.len() > 1 => {
-                    let mut list = state
-                        .project_type_list
-                        .expect("should be populated by this point");
-
-                    list.set_items(available_types.clone());
-                    list.draw(frame, layout_chunks[1], "Project types");
-                    state.project_type_list = Some(list);
-                },
-                RunningConfigMessage::PromptForProjectType {
-                    available_types, ..
-                } => {
-                    state.selected_project_type = available_types.first().cloned();
-                },
-                RunningConfigMessage::NoOp => (),
+    match message {
+        RunningConfigMessage::SetCommandStepText(text) => {
+            state.finish_running_step(StepStatus::Done);
+            state.step_log.push((text.clone(), StepStatus::Running));
+            state.step_name = text;
+        },
+        RunningConfigMessage::StepSkipped(name) => {
+            state.finish_running_step(StepStatus::Done);
+            state.step_log.push((name, StepStatus::Skipped));
+        },
+        RunningConfigMessage::AlreadyAnswered { label, answer } =>
+            state.log(format!("{label}: {answer} (already answered)")),
+        RunningConfigMessage::CommandOutput(output) => state.log(output),
+        RunningConfigMessage::CommandExited(status) =>
+            state.log(format!("Command exited with {status}")),
+        RunningConfigMessage::Planned(action) => state.log(format!("Would {action}")),
+        RunningConfigMessage::StepFailed(error) => {
+            state.finish_running_step(StepStatus::Failed);
+            state.log(format!("Step failed: {error}"));
+        },
+        RunningConfigMessage::Effects(effects) => {
+            state.log(String::from("The run made these changes:"));
+            effects
+                .iter()
+                .for_each(|effect| state.log(format!("  {effect}")));
+        },
+        RunningConfigMessage::Failed(error) => {
+            if let RunnerError::UnmetRequirements(ref unmet) = error {
+                unmet.iter().for_each(|unmet| state.log(unmet.to_string()));
             }
 
-            let paragraph_text = state.scroll_back.join("\n");
-            let scrollback_output = Paragraph::new(paragraph_text).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(format!("Step: {}", state.step_name))
-                    .title_alignment(Alignment::Center),
-            );
+            state.finish_running_step(StepStatus::Failed);
+            state.failure = Some(error.to_string());
+        },
+        RunningConfigMessage::Finished => {
+            state.finish_running_step(StepStatus::Done);
+            state.finished = true;
+        },
+    }
+}
 
-            frame.render_widget(scrollback_output, layout_chunks[0]);
+/// Shows a question of the run on the running screen, until it's answered by
+/// [`handle_prompt_events`].
+fn start_prompt<ListItem>(
+    app_state: &mut AppState<ListItem>,
+    question: Question,
+    channel: Sender<Answer>,
+) where
+    for<'a> ListItem: StatefulListItem<'a>,
+{
+    let AppState::Running(_, ref mut state) = app_state else {
+        return;
+    };
 
-            *running_state = Some(state);
+    let (options, selected) = match question {
+        Question::Text { .. } => (None, 0),
+        Question::Choice {
+            ref options,
+            default,
+            ..
+        } => (Some(prompt_options(options, None)), default),
+        Question::Confirm { default, .. } => {
+            let options = [String::from("Yes"), String::from("No")];
+
+            (Some(prompt_options(&options, None)), usize::from(!default))
+        },
+        Question::MultiSelect {
+            ref options,
+            ref default,
+            ..
+        } => {
+            state.prompt_checked = default.clone();
+
+            (Some(prompt_options(options, Some(default))), 0)
+        },
+    };
 
-            if should_stop {
-                return Some(AppState::Stopping);
-            }
+    state.input.reset();
+    state.input_error = None;
+    state.prompt_list = options.map(|options| {
+        let mut list = StatefulList::new(options);
+        list.select(selected);
+        list
+    });
+    state.prompt = Some((question, channel));
+}
+
+fn ui_running(frame: &mut Frame, state: &mut RunningState) {
+    let prompt_height = match (&state.prompt_list, &state.prompt) {
+        (Some(list), _) => list.get_items().len() as u16 + 2,
+        (None, Some(_)) => 3,
+        (None, None) => 0,
+    };
+    let layout_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(prompt_height)])
+        .margin(2)
+        .split(frame.size());
+    let output_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+        .split(layout_chunks[0]);
+
+    let step_log = state
+        .step_log
+        .iter()
+        .map(|(name, status)| {
+            let style = match status {
+                StepStatus::Skipped => Style::default().fg(Color::DarkGray),
+                StepStatus::Failed => Style::default().fg(Color::Red),
+                _ => Style::default(),
+            };
+
+            Line::styled(format!("[{}] {name}", status.symbol()), style)
+        })
+        .collect::<Vec<_>>();
+    let step_log_output =
+        Paragraph::new(step_log).block(Block::default().borders(Borders::ALL).title("Steps"));
+
+    frame.render_widget(step_log_output, output_chunks[0]);
+
+    // only the lines that fit are drawn, so that the latest output is always shown.
+    let visible_lines = output_chunks[1].height.saturating_sub(2) as usize;
+    let paragraph_text = state
+        .scroll_back
+        .iter()
+        .skip(state.scroll_back.len().saturating_sub(visible_lines))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let block = match state.failure {
+        Some(ref failure) => Block::default()
+            .title(format!("Failed: {failure} (press q to quit)"))
+            .border_style(Style::default().fg(Color::Red)),
+        None if state.finished => Block::default()
+            .title("Done (press q to quit)")
+            .border_style(Style::default().fg(Color::Green)),
+        None => Block::default().title(format!("Step: {} (c or q to cancel)", state.step_name)),
+    };
+    let scrollback_output = Paragraph::new(paragraph_text).block(
+        block
+            .borders(Borders::ALL)
+            .title_alignment(Alignment::Center),
+    );
+
+    frame.render_widget(scrollback_output, output_chunks[1]);
+
+    if let Some((ref question, _)) = state.prompt {
+        let title = match question {
+            Question::Text {
+                label,
+                default: Some(default),
+                ..
+            } => format!("{label} (default: {default})"),
+            Question::MultiSelect { label, .. } => format!("{label} (space to select)"),
+            _ => question.label().to_owned(),
+        };
+
+        match state.prompt_list {
+            Some(ref mut list) => list.draw(frame, layout_chunks[1], title),
+            None => state
+                .input
+                .draw(frame, layout_chunks[1], title, state.input_error.as_deref()),
         }
-    } else if let Err(_error) = res {
-        cleanup().unwrap();
-        panic!("could not receive from command: {_error}")
     }
-
-    None
 }
 
-/// Draw the ui of the application.
-fn ui<ListItem>(frame: &mut Frame, app_state: &mut AppState<ListItem>)
-where
+/// Draw the ui of the application. Drawing never waits on anything, as the events that
+/// change what is drawn are handled in the main loop.
+fn ui<ListItem>(
+    frame: &mut Frame,
+    app_state: &mut AppState<ListItem>,
+    diagnostics: &[PluginDiagnostic],
+) where
     for<'a> ListItem: StatefulListItem<'a>,
 {
     match app_state {
-        AppState::Main(ref mut list) => list.draw(
-            frame,
-            frame.size(),
-            String::from("Please choose a language"),
-        ),
-        AppState::Starting(ref config) => {
-            let runner = config.create_runner();
-
-            *app_state = AppState::Running(runner, None);
-        },
-        AppState::Running(ref mut runner, ref mut running_state) => {
-            if let Some(new_state) = ui_running(frame, runner, running_state) {
-                *app_state = new_state;
+        AppState::Main(ref mut list) => {
+            let problems = diagnostics
+                .iter()
+                .flat_map(|diagnostic| {
+                    diagnostic
+                        .to_string()
+                        .lines()
+                        .map(|line| Line::raw(line.to_owned()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            // the problems take up to half of the screen, and nothing if there are none.
+            let problems_height = match problems.len() {
+                0 => 0,
+                lines => (lines as u16 + 2).min(frame.size().height / 2),
+            };
+            let layout_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Fill(1), Constraint::Length(problems_height)])
+                .split(frame.size());
+
+            list.draw(
+                frame,
+                layout_chunks[0],
+                String::from(
+                    "Please choose a language (enter to run, d for a dry run, a to add to the \
+                     project here)",
+                ),
+            );
+
+            if !problems.is_empty() {
+                let title = format!(
+                    "Plugin problems ({}, see `proman plugins`)",
+                    diagnostics.len()
+                );
+                let panel = Paragraph::new(problems).wrap(Wrap { trim: false }).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Red))
+                        .title(title),
+                );
+
+                frame.render_widget(panel, layout_chunks[1]);
             }
         },
-        AppState::Stopping => (),
+        AppState::Running(_, ref mut running_state) => ui_running(frame, running_state),
     }
 }
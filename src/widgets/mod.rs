@@ -1,3 +1,5 @@
 mod stateful_list;
+mod text_input;
 
 pub(crate) use stateful_list::*;
+pub(crate) use text_input::*;
@@ -74,6 +74,14 @@ where
 
     pub(crate) fn get_selected_index(&self) -> usize { self.selected_index }
 
+    pub(crate) fn select(&mut self, index: usize) {
+        self.selected_index = index.min(self.items.len().saturating_sub(1));
+        self.list_state = self
+            .list_state
+            .clone()
+            .with_selected(Some(self.selected_index));
+    }
+
     pub(crate) fn draw<'b, S: 'b>(&mut self, frame: &mut Frame, area: Rect, title: S)
     where
         Text<'b>: From<S>,
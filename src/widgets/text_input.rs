@@ -0,0 +1,198 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use tui_input::{backend::crossterm::EventHandler, Input, InputRequest};
+
+/// What a [`TextInput`] did with an event given to [`TextInput::handle_event`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum InputOutcome {
+    /// The text was edited or the cursor was moved.
+    Changed,
+    /// Enter was pressed, so the text should be submitted.
+    Submitted,
+    /// Escape was pressed, so the input should be given up on.
+    Cancelled,
+    /// The event had nothing to do with the input.
+    Ignored,
+}
+
+/// A single line of editable text with a cursor. Besides the usual editing keys, words
+/// can be moved over with ctrl or alt and the arrow keys, and deleted with ctrl+w or
+/// alt+backspace. Text can also be pasted, if the terminal has bracketed paste enabled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TextInput {
+    input: Input,
+}
+
+impl TextInput {
+    pub(crate) fn value(&self) -> &str { self.input.value() }
+
+    pub(crate) fn reset(&mut self) { self.input.reset(); }
+
+    pub(crate) fn handle_event(&mut self, event: &Event) -> InputOutcome {
+        let request = match event {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) => match (*code, *modifiers) {
+                (KeyCode::Enter, _) => return InputOutcome::Submitted,
+                (KeyCode::Esc, _) => return InputOutcome::Cancelled,
+                // tui-input only understands these with meta, which most terminals send as
+                // alt instead.
+                (KeyCode::Left | KeyCode::Char('b'), KeyModifiers::ALT) =>
+                    Some(InputRequest::GoToPrevWord),
+                (KeyCode::Right | KeyCode::Char('f'), KeyModifiers::ALT) =>
+                    Some(InputRequest::GoToNextWord),
+                (KeyCode::Backspace, KeyModifiers::ALT | KeyModifiers::CONTROL) =>
+                    Some(InputRequest::DeletePrevWord),
+                (KeyCode::Char('d'), KeyModifiers::ALT) => Some(InputRequest::DeleteNextWord),
+                _ => None,
+            },
+            Event::Paste(text) => {
+                // the input is a single line, so line breaks and other control characters
+                // are left out.
+                text.chars()
+                    .filter(|character| !character.is_control())
+                    .for_each(|character| {
+                        self.input.handle(InputRequest::InsertChar(character));
+                    });
+
+                return InputOutcome::Changed;
+            },
+            _ => None,
+        };
+
+        let changed = match request {
+            Some(request) => self.input.handle(request),
+            None => self.input.handle_event(event),
+        };
+
+        match changed {
+            Some(_) => InputOutcome::Changed,
+            None => InputOutcome::Ignored,
+        }
+    }
+
+    /// Draws the input in a bordered block with `title`, scrolled so that the cursor is
+    /// always visible. If there is an `error`, it's shown under the input.
+    pub(crate) fn draw(&self, frame: &mut Frame, area: Rect, title: String, error: Option<&str>) {
+        let mut block = Block::default().borders(Borders::ALL).title(title);
+
+        if let Some(error) = error {
+            block = block
+                .title_bottom(format!("Invalid: {error}"))
+                .border_style(Style::default().fg(Color::Red));
+        }
+
+        let width = area.width.saturating_sub(2).max(1);
+        let scroll = self.input.visual_scroll(width as usize - 1);
+        let input = Paragraph::new(self.input.value())
+            .scroll((0, scroll as u16))
+            .block(block);
+
+        frame.render_widget(input, area);
+        frame.set_cursor(
+            area.x + 1 + (self.input.visual_cursor() - scroll) as u16,
+            area.y + 1,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        for character in text.chars() {
+            input.handle_event(&key(KeyCode::Char(character), KeyModifiers::NONE));
+        }
+        input
+    }
+
+    #[test]
+    fn inserts_at_the_cursor() {
+        let mut input = typed("helo");
+
+        input.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Char('l'), KeyModifiers::NONE));
+        assert_eq!(input.value(), "hello");
+        assert_eq!(input.input.cursor(), 4);
+
+        input.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Char('>'), KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::End, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Char('!'), KeyModifiers::NONE));
+        assert_eq!(input.value(), ">hello!");
+    }
+
+    #[test]
+    fn deletes_around_the_cursor() {
+        let mut input = typed("abcd");
+
+        input.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(input.value(), "acd");
+        input.handle_event(&key(KeyCode::Delete, KeyModifiers::NONE));
+        assert_eq!(input.value(), "ad");
+        assert_eq!(input.input.cursor(), 1);
+    }
+
+    #[test]
+    fn moves_and_deletes_by_word() {
+        let mut input = typed("one two three");
+
+        input.handle_event(&key(KeyCode::Left, KeyModifiers::ALT));
+        assert_eq!(input.input.cursor(), 8);
+        input.handle_event(&key(KeyCode::Char('b'), KeyModifiers::ALT));
+        assert_eq!(input.input.cursor(), 4);
+        input.handle_event(&key(KeyCode::Char('d'), KeyModifiers::ALT));
+        assert_eq!(input.value(), "one three");
+
+        input.handle_event(&key(KeyCode::End, KeyModifiers::NONE));
+        input.handle_event(&key(KeyCode::Backspace, KeyModifiers::ALT));
+        assert_eq!(input.value(), "one ");
+        input.handle_event(&key(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), "");
+    }
+
+    #[test]
+    fn pastes_a_single_line() {
+        let mut input = typed("a");
+
+        assert_eq!(
+            input.handle_event(&Event::Paste(String::from("b\nc\td"))),
+            InputOutcome::Changed
+        );
+        assert_eq!(input.value(), "abcd");
+        assert_eq!(input.input.cursor(), 4);
+    }
+
+    #[test]
+    fn submits_and_cancels() {
+        let mut input = typed("name");
+
+        assert_eq!(
+            input.handle_event(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            InputOutcome::Submitted
+        );
+        assert_eq!(
+            input.handle_event(&key(KeyCode::Esc, KeyModifiers::NONE)),
+            InputOutcome::Cancelled
+        );
+        assert_eq!(
+            input.handle_event(&key(KeyCode::F(1), KeyModifiers::NONE)),
+            InputOutcome::Ignored
+        );
+        assert_eq!(input.value(), "name");
+
+        input.reset();
+        assert_eq!(input.value(), "");
+    }
+}